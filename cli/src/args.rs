@@ -1,6 +1,7 @@
-use std::fmt;
+use std::{fmt, path::PathBuf};
 
 use clap::{Args, Parser, Subcommand};
+use conditions::config::MIN_WATCH_INTERVAL_SECS;
 use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Debug, Parser)]
@@ -8,6 +9,16 @@ use serde::{Deserialize, Deserializer, Serialize};
 pub struct Conditions {
     #[clap(subcommand)]
     pub command: Command,
+    /// How to report errors: text on stderr or a JSON object on stdout
+    #[clap(long, global = true, value_enum, default_value_t)]
+    pub output: OutputFormat,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
 }
 
 #[derive(Debug, Subcommand)]
@@ -15,13 +26,119 @@ pub enum Command {
     /// View configuration information
     Config(ConfigCommand),
     /// Get the current weather conditions (optional provide location)
-    Current { region: Option<String> },
+    Current(CurrentCommand),
+    /// Get the current air quality (optional provide location)
+    Air(RegionArgs),
+    /// Get the current pollen counts (optional provide location)
+    Pollen(RegionArgs),
+    /// Get the active weather alerts (optional provide location)
+    Alerts(RegionArgs),
+    /// Get sunrise, sunset, twilight and the moon phase, computed offline
+    /// (optional provide location)
+    Astro(RegionArgs),
+    /// Evaluate the configured rules periodically, running the hook when
+    /// one fires
+    Watch(WatchCommand),
     /// Location conditions apply to
     Location(LocationCommand),
     /// weatherapi.com api-key
     WeatherApiKey(WeatherApiKeyCommand),
     /// Weather unit, celsius or fahrenheit
     Unit(UnitCommand),
+    /// Offline geocoding data
+    Geodata(GeodataCommand),
+    /// Inspect and clean up cached locations
+    Cache(CacheCommand),
+    /// Conditions recorded by previous fetches
+    History(HistoryCommand),
+}
+
+// flags are naturally bools
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Args)]
+pub struct CurrentCommand {
+    /// Postal code and country, place name, geo URI or @name: example -
+    /// 10001,usa, "Lisbon, PT", geo:35.15,-80.81 or @office. Several
+    /// regions, or - to read them from stdin (one per line), return an
+    /// array
+    #[clap(id = "region", value_name = "REGION")]
+    pub regions: Vec<String>,
+    #[clap(flatten)]
+    pub coordinates: Coordinates,
+    /// Which match to use when a place name is ambiguous (1-based)
+    #[clap(long)]
+    pub pick: Option<usize>,
+    /// Include the location and its name in the output
+    #[clap(long)]
+    pub show_location: bool,
+    /// Include the air quality index and pollutants in the output
+    #[clap(long)]
+    pub air_quality: bool,
+    /// Include the pollen counts and their severity in the output
+    #[clap(long)]
+    pub pollen: bool,
+    /// Include the active weather alerts in the output
+    #[clap(long)]
+    pub alerts: bool,
+    /// Include sunrise, sunset, twilight and the moon phase in the output
+    #[clap(long)]
+    pub astro: bool,
+    /// Locate the machine again, ignoring the stored location and the
+    /// cached IP-derived one
+    #[clap(long, conflicts_with_all = ["region", "lat"])]
+    pub relocate: bool,
+    /// Format of the conditions, waybar includes the alerts and only
+    /// supports a single region
+    #[clap(long, value_enum, default_value_t)]
+    pub format: CurrentFormat,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum CurrentFormat {
+    #[default]
+    Json,
+    /// JSON for a waybar custom module (`return-type: json`)
+    Waybar,
+}
+
+/// The region a command applies to, shared by the commands taking a single
+/// optional region.
+#[derive(Debug, Args)]
+pub struct RegionArgs {
+    /// Postal code and country, place name, geo URI or @name, defaults to
+    /// the configured or detected location
+    #[clap(id = "region", value_name = "REGION")]
+    pub region: Option<String>,
+    #[clap(flatten)]
+    pub coordinates: Coordinates,
+    /// Which match to use when a place name is ambiguous (1-based)
+    #[clap(long)]
+    pub pick: Option<usize>,
+}
+
+impl RegionArgs {
+    /// Returns the requested region, `None` for the configured or detected
+    /// location.
+    pub fn region(&self) -> Option<String> {
+        self.coordinates.or_region(self.region.as_ref())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct WatchCommand {
+    /// Postal code and country, place name, geo URI or @name, defaults to
+    /// the configured or detected location
+    #[clap(id = "region", value_name = "REGION")]
+    pub region: Option<String>,
+    #[clap(flatten)]
+    pub coordinates: Coordinates,
+    /// Time between evaluations, at least a minute, defaults to
+    /// `watch_interval_secs`: example - 90s, 10m, 1h
+    #[clap(long, value_parser = parse_interval)]
+    pub interval: Option<u64>,
+    /// Evaluate the rules once and exit
+    #[clap(long)]
+    pub once: bool,
 }
 
 #[derive(Debug, Args)]
@@ -74,12 +191,68 @@ pub enum LocationSubcommand {
     View,
     /// Unset current location
     Unset,
+    /// Save a named location, use it as @name in place of a region
+    Add(AddLocation),
+    /// Remove a named location
+    Remove(NamedLocation),
+    /// List named locations
+    List,
+    /// Use a named location when no region is given
+    Default(NamedLocation),
+}
+
+#[derive(Debug, Args)]
+pub struct AddLocation {
+    /// Name to save the location under: example - office
+    pub name: String,
+    #[clap(flatten)]
+    pub location: SetLocation,
+}
+
+#[derive(Debug, Args)]
+pub struct NamedLocation {
+    /// Name the location was saved under: example - office
+    pub name: String,
 }
 
 #[derive(Debug, Args)]
 pub struct SetLocation {
-    /// Postal code and country to retrieve weather for: example - 10001,usa
-    pub region: String,
+    /// Postal code and country, place name or geo URI to retrieve weather
+    /// for: example - 10001,usa, "Lisbon, PT" or geo:35.15,-80.81
+    #[clap(required_unless_present = "lat")]
+    pub region: Option<String>,
+    #[clap(flatten)]
+    pub coordinates: Coordinates,
+    /// Which match to use when a place name is ambiguous (1-based)
+    #[clap(long)]
+    pub pick: Option<usize>,
+}
+
+/// Latitude and longitude given as separate flags instead of a region.
+#[derive(Debug, Args)]
+pub struct Coordinates {
+    /// Latitude in decimal degrees: example - 35.15
+    #[clap(
+        long,
+        requires = "lon",
+        conflicts_with = "region",
+        allow_negative_numbers = true
+    )]
+    pub lat: Option<f64>,
+    /// Longitude in decimal degrees: example - -80.81
+    #[clap(long, requires = "lat", allow_negative_numbers = true)]
+    pub lon: Option<f64>,
+}
+
+impl Coordinates {
+    /// Returns the coordinates as a `geo:` URI when both were given, otherwise
+    /// `region`.
+    pub fn or_region(&self, region: Option<&String>) -> Option<String> {
+        match (self.lat, self.lon) {
+            (Some(lat), Some(lon)) => Some(format!("geo:{lat},{lon}")),
+            _ => region.cloned(),
+        }
+    }
 }
 
 #[derive(Debug, Args)]
@@ -103,6 +276,139 @@ pub struct SetUnit {
     pub unit: Unit,
 }
 
+#[derive(Debug, Args)]
+pub struct GeodataCommand {
+    #[clap(subcommand)]
+    pub command: GeodataSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum GeodataSubcommand {
+    /// Import a postal code dump from download.geonames.org/export/zip (eg.
+    /// US.txt, unzipped) so that postal codes are found offline
+    Import(ImportGeodata),
+}
+
+#[derive(Debug, Args)]
+pub struct ImportGeodata {
+    /// Path to the tab separated dump
+    pub file: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct CacheCommand {
+    #[clap(subcommand)]
+    pub command: CacheSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CacheSubcommand {
+    /// Print the path to the cache database
+    Path,
+    /// List cached locations with their age
+    List,
+    /// Remove every cached location
+    Clear,
+    /// Remove cached locations by key or age
+    Prune(PruneCache),
+    /// Show entry counts and hit/miss counters
+    Stats,
+}
+
+#[derive(Debug, Args)]
+pub struct PruneCache {
+    /// Keys of the entries to remove, as shown by `cache list`
+    #[clap(required_unless_present = "older_than")]
+    pub keys: Vec<String>,
+    /// Remove entries older than this age: example - 90s, 30m, 12h, 7d
+    #[clap(long, value_parser = parse_age)]
+    pub older_than: Option<u64>,
+}
+
+#[derive(Debug, Args)]
+#[clap(args_conflicts_with_subcommands = true)]
+pub struct HistoryCommand {
+    #[clap(subcommand)]
+    pub command: Option<HistorySubcommand>,
+    /// Only include observations newer than this age: example - 12h, 7d
+    #[clap(long, value_parser = parse_age)]
+    pub since: Option<u64>,
+    /// Format of the observations
+    #[clap(long, value_enum, default_value_t)]
+    pub format: HistoryFormat,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum HistorySubcommand {
+    /// Fetch daily and hourly records from the Open-Meteo archive into the
+    /// cache database and print them
+    Fetch(FetchHistory),
+}
+
+#[derive(Debug, Args)]
+pub struct FetchHistory {
+    /// Postal code and country, place name, geo URI or @name, defaults to
+    /// the configured or detected location
+    #[clap(id = "region", value_name = "REGION")]
+    pub region: Option<String>,
+    #[clap(flatten)]
+    pub coordinates: Coordinates,
+    /// First day to fetch: example - 2024-01-01
+    #[clap(long, value_parser = parse_date)]
+    pub from: String,
+    /// Last day to fetch (inclusive): example - 2024-01-31
+    #[clap(long, value_parser = parse_date)]
+    pub to: String,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default)]
+pub enum HistoryFormat {
+    Csv,
+    #[default]
+    Json,
+}
+
+/// Parses an age such as `90s`, `30m`, `12h`, `7d` or `2w` into seconds.
+pub fn parse_age(age: &str) -> Result<u64, String> {
+    let invalid =
+        || format!("invalid age \"{age}\", expect eg. 30m, 12h or 7d");
+    let (split, unit) = age.char_indices().next_back().ok_or_else(invalid)?;
+    let count = age[..split].parse::<u64>().map_err(|_| invalid())?;
+    let multiplier = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        'w' => 7 * 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+
+    count.checked_mul(multiplier).ok_or_else(invalid)
+}
+
+/// Parses a watch interval like `parse_age`, refusing intervals shorter than
+/// `MIN_WATCH_INTERVAL_SECS`.
+pub fn parse_interval(interval: &str) -> Result<u64, String> {
+    let secs = parse_age(interval)?;
+
+    if secs < MIN_WATCH_INTERVAL_SECS {
+        return Err(format!(
+            "interval \"{interval}\" is shorter than {MIN_WATCH_INTERVAL_SECS}s"
+        ));
+    }
+
+    Ok(secs)
+}
+
+/// Checks that `date` is a `YYYY-MM-DD` calendar date.
+pub fn parse_date(date: &str) -> Result<String, String> {
+    if conditions::history::is_date(date) {
+        Ok(date.to_string())
+    } else {
+        Err(format!("invalid date \"{date}\", expect YYYY-MM-DD"))
+    }
+}
+
 #[derive(clap::ValueEnum, Clone, Copy, Debug, Default, Serialize)]
 pub enum Unit {
     C,
@@ -141,3 +447,51 @@ impl<'de> Deserialize<'de> for Unit {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_ages() {
+        assert_eq!(parse_age("90s"), Ok(90));
+        assert_eq!(parse_age("30m"), Ok(1_800));
+        assert_eq!(parse_age("12h"), Ok(43_200));
+        assert_eq!(parse_age("7d"), Ok(604_800));
+        assert_eq!(parse_age("2w"), Ok(1_209_600));
+    }
+
+    #[test]
+    fn it_rejects_invalid_ages() {
+        for age in [
+            "",
+            "7",
+            "d",
+            "7д",
+            "д",
+            "7y",
+            "-7d",
+            "40000000000000w",
+            "99999999999999999999s",
+        ] {
+            assert!(parse_age(age).is_err(), "{age} was accepted");
+        }
+    }
+
+    #[test]
+    fn it_parses_intervals() {
+        assert_eq!(parse_interval("60s"), Ok(60));
+        assert_eq!(parse_interval("10m"), Ok(600));
+        assert!(parse_interval("0s").is_err());
+        assert!(parse_interval("59s").is_err());
+    }
+
+    #[test]
+    fn it_parses_dates() {
+        assert_eq!(parse_date("2024-02-29"), Ok("2024-02-29".to_string()));
+
+        for date in ["", "2024-1-01", "2023-02-29", "2024-13-01", "yesterday"] {
+            assert!(parse_date(date).is_err(), "{date} was accepted");
+        }
+    }
+}
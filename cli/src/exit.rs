@@ -0,0 +1,269 @@
+use std::process::ExitCode;
+
+use serde::Serialize;
+
+/// Reasons the CLI can exit unsuccessfully, each with a distinct exit code so
+/// that scripts can tell failures apart.
+///
+/// Code `2` is left to `clap` which uses it for invalid arguments.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Failure {
+    /// Anything not covered below (eg. cache or serialization errors).
+    Other,
+    /// A provider could not be reached, rejected or garbled the request.
+    Network,
+    /// Configuration is missing or invalid, including api keys.
+    Config,
+    /// The requested location is malformed or could not be found.
+    InvalidLocation,
+    /// Every weather provider was tried and none succeeded.
+    AllProvidersFailed,
+    /// No location source or ip geolocation provider could tell where the
+    /// machine is.
+    LocationUnavailable,
+}
+
+impl Failure {
+    #[must_use]
+    pub fn code(self) -> u8 {
+        match self {
+            Failure::Other => 1,
+            Failure::Network => 3,
+            Failure::Config => 4,
+            Failure::InvalidLocation => 5,
+            Failure::AllProvidersFailed => 6,
+            Failure::LocationUnavailable => 7,
+        }
+    }
+}
+
+impl From<&eyre::Report> for Failure {
+    fn from(report: &eyre::Report) -> Self {
+        report
+            .downcast_ref::<conditions::Error>()
+            .map_or(Failure::Other, Failure::from)
+    }
+}
+
+impl From<&conditions::Error> for Failure {
+    fn from(err: &conditions::Error) -> Self {
+        match err {
+            conditions::Error::Network { .. }
+            | conditions::Error::Parse { .. }
+            | conditions::Error::RateLimited { .. } => Failure::Network,
+            conditions::Error::InvalidApiKey { .. }
+            | conditions::Error::Unavailable { .. }
+            | conditions::Error::Config(_) => Failure::Config,
+            conditions::Error::InvalidLocation(_)
+            | conditions::Error::LocationNotFound(_)
+            | conditions::Error::AmbiguousLocation { .. } => {
+                Failure::InvalidLocation
+            }
+            conditions::Error::AllProvidersFailed(_) => {
+                Failure::AllProvidersFailed
+            }
+            conditions::Error::LocationUnavailable(_) => {
+                Failure::LocationUnavailable
+            }
+            conditions::Error::Cache(_)
+            | conditions::Error::Read { .. }
+            | conditions::Error::Hook { .. }
+            | conditions::Error::InvalidDate(_) => Failure::Other,
+        }
+    }
+}
+
+impl From<Failure> for ExitCode {
+    fn from(failure: Failure) -> Self {
+        ExitCode::from(failure.code())
+    }
+}
+
+/// Machine readable representation of an error, printed to stdout when
+/// `--output json` is used.
+///
+/// ```json
+/// {"error":{"kind":"network","message":"...","exit_code":3}}
+/// ```
+#[derive(Debug, Serialize)]
+pub struct ErrorOutput {
+    error: ErrorDetail,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorDetail {
+    kind: Failure,
+    message: String,
+    exit_code: u8,
+}
+
+impl ErrorDetail {
+    #[must_use]
+    pub fn new(failure: Failure, message: String) -> Self {
+        Self {
+            kind: failure,
+            message,
+            exit_code: failure.code(),
+        }
+    }
+}
+
+impl ErrorOutput {
+    #[must_use]
+    pub fn new(failure: Failure, report: &eyre::Report) -> Self {
+        Self {
+            error: ErrorDetail::new(failure, report.to_string()),
+        }
+    }
+}
+
+/// One element of the array printed when querying several regions at once.
+///
+/// ```json
+/// [
+///   {"region":"10001,US","temp":57,"icon":"","location":{...}},
+///   {"region":"@nowhere","error":{"kind":"invalid_location",...}}
+/// ]
+/// ```
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum BatchEntry {
+    Success {
+        region: String,
+        #[serde(flatten)]
+        output: Box<conditions::conditions::Output>,
+    },
+    Failure {
+        region: String,
+        error: ErrorDetail,
+    },
+}
+
+impl BatchEntry {
+    #[must_use]
+    pub fn new(
+        region: String,
+        result: conditions::Result<conditions::conditions::Output>,
+    ) -> Self {
+        match result {
+            Ok(output) => BatchEntry::Success {
+                region,
+                output: Box::new(output),
+            },
+            Err(err) => BatchEntry::Failure {
+                region,
+                error: ErrorDetail::new(Failure::from(&err), err.to_string()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network() -> conditions::Error {
+        conditions::Error::Network {
+            provider: "open_meteo".to_string(),
+            status: Some(503),
+        }
+    }
+
+    #[test]
+    fn it_maps_errors_to_failures() {
+        let cases = [
+            (network(), Failure::Network, 3),
+            (
+                conditions::Error::RateLimited {
+                    provider: "weatherapi".to_string(),
+                },
+                Failure::Network,
+                3,
+            ),
+            (
+                conditions::Error::InvalidApiKey {
+                    provider: "weatherapi".to_string(),
+                },
+                Failure::Config,
+                4,
+            ),
+            (
+                conditions::Error::LocationNotFound("nowhere".to_string()),
+                Failure::InvalidLocation,
+                5,
+            ),
+            (
+                conditions::Error::AllProvidersFailed(vec![network()]),
+                Failure::AllProvidersFailed,
+                6,
+            ),
+            (
+                conditions::Error::LocationUnavailable(vec![network()]),
+                Failure::LocationUnavailable,
+                7,
+            ),
+            (
+                conditions::Error::Hook {
+                    command: "false".to_string(),
+                    message: "exit status 1".to_string(),
+                },
+                Failure::Other,
+                1,
+            ),
+        ];
+
+        for (err, failure, code) in cases {
+            assert_eq!(Failure::from(&err), failure, "{err}");
+            assert_eq!(failure.code(), code);
+        }
+    }
+
+    #[test]
+    fn it_finds_library_errors_in_reports() {
+        let report = eyre::Report::new(network()).wrap_err("fetching");
+
+        assert_eq!(Failure::from(&report), Failure::Network);
+        assert_eq!(
+            Failure::from(&eyre::eyre!("something else")),
+            Failure::Other
+        );
+    }
+
+    #[test]
+    fn it_serializes_error_output() {
+        let report = eyre::Report::new(network());
+        let output = ErrorOutput::new(Failure::from(&report), &report);
+
+        assert_eq!(
+            serde_json::to_value(output).unwrap(),
+            serde_json::json!({
+                "error": {
+                    "kind": "network",
+                    "message": "request to open_meteo failed with status 503",
+                    "exit_code": 3,
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn it_serializes_failed_batch_entries() {
+        let entry = BatchEntry::new(
+            "@nowhere".to_string(),
+            Err(conditions::Error::LocationNotFound("@nowhere".to_string())),
+        );
+
+        assert_eq!(
+            serde_json::to_value(entry).unwrap(),
+            serde_json::json!({
+                "region": "@nowhere",
+                "error": {
+                    "kind": "invalid_location",
+                    "message": "location not found: @nowhere",
+                    "exit_code": 5,
+                }
+            })
+        );
+    }
+}
@@ -1,32 +1,67 @@
 #![deny(clippy::pedantic)]
 
-use clap::Parser;
+use std::{
+    io::{self, BufRead, IsTerminal, Write},
+    process::ExitCode,
+    time::Duration,
+};
+
+use clap::{error::ErrorKind, CommandFactory, Parser};
 
 mod args;
+mod exit;
 
 use args::{
-    Command, Conditions, ConfigSubcommand, LocationSubcommand, UnitSubcommand,
-    WeatherApiKeySubcommand,
+    CacheSubcommand, Command, Conditions, ConfigSubcommand, CurrentCommand,
+    CurrentFormat, GeodataSubcommand, HistoryCommand, HistoryFormat,
+    HistorySubcommand, LocationSubcommand, OutputFormat, RegionArgs,
+    UnitSubcommand, WatchCommand, WeatherApiKeySubcommand,
 };
 use conditions::{cache::Cache, config::Config};
+use exit::{BatchEntry, ErrorOutput, Failure};
 
 #[tokio::main(flavor = "current_thread")]
-async fn main() {
+async fn main() -> ExitCode {
     env_logger::init();
 
-    match run().await {
-        Ok(result) => println!("{result}"),
+    let args = Conditions::parse();
+
+    match run(&args).await {
+        Ok(result) => {
+            // watch prints its events as they happen
+            if !result.is_empty() {
+                println!("{result}");
+            }
+
+            ExitCode::SUCCESS
+        }
         Err(err) => {
+            let failure = Failure::from(&err);
+
             // for user
-            eprintln!("{err}");
+            match args.output {
+                OutputFormat::Text => eprintln!("{err}"),
+                OutputFormat::Json => match serde_json::to_string(
+                    &ErrorOutput::new(failure, &err),
+                ) {
+                    Ok(json) => println!("{json}"),
+                    Err(_) => eprintln!("{err}"),
+                },
+            }
             // for development
             log::error!("{:?}", err);
+
+            failure.into()
         }
     }
 }
 
 /// Entry point for running the application logic based on parsed CLI arguments.
 ///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments.
+///
 /// # Returns
 ///
 /// - `Ok(String)`: A success message or relevant output for the executed
@@ -40,36 +75,19 @@ async fn main() {
 /// - Failure to read or write to the config file.
 /// - Failure to fetch weather conditions.
 /// - Failure to manage location, weather API key, or unit settings.
-pub async fn run() -> eyre::Result<String> {
-    let args = Conditions::parse();
-
+pub async fn run(args: &Conditions) -> eyre::Result<String> {
     let result = match &args.command {
         Command::Config(cmd) => match &cmd.command {
             ConfigSubcommand::Path => Config::location()?,
             ConfigSubcommand::View => Config::view()?,
         },
-        Command::Current { region } => {
-            let (config, mut cache) = init().await?;
-
-            let output = conditions::Conditions::new(config, region.clone())
-                .fetch(&mut cache)
-                .await?;
-
-            serde_json::to_string(&output)?
-        }
-        Command::Location(cmd) => match &cmd.command {
-            LocationSubcommand::Set(input) => {
-                let (mut config, mut cache) = init().await?;
-
-                config.set_location(&mut cache, &input.region).await?
-            }
-            LocationSubcommand::View => {
-                let (mut config, mut cache) = init().await?;
-
-                config.get_location(&mut cache).await?.to_string()
-            }
-            LocationSubcommand::Unset => Config::unset_location()?,
-        },
+        Command::Current(cmd) => current(cmd).await?,
+        Command::Air(cmd) => air(cmd).await?,
+        Command::Pollen(cmd) => pollen(cmd).await?,
+        Command::Alerts(cmd) => alerts(cmd).await?,
+        Command::Astro(cmd) => astro(cmd).await?,
+        Command::Watch(cmd) => watch(cmd).await?,
+        Command::Location(cmd) => location(&cmd.command).await?,
         Command::WeatherApiKey(cmd) => match &cmd.command {
             WeatherApiKeySubcommand::Set(input) => {
                 Config::set_weatherapi_token(&input.key)?
@@ -84,17 +102,543 @@ pub async fn run() -> eyre::Result<String> {
         Command::Unit(cmd) => match &cmd.command {
             UnitSubcommand::Set(unit) => Config::set_unit(unit.unit.to())?,
             UnitSubcommand::View => {
-                format!("unit stored as: {}", Config::load()?.unit)
+                format!(
+                    "unit stored as: {}",
+                    Config::load()?.units().temperature
+                )
             }
         },
+        Command::Cache(cmd) => cache(&cmd.command).await?,
+        Command::History(cmd) => history(cmd).await?,
+        Command::Geodata(cmd) => match &cmd.command {
+            GeodataSubcommand::Import(input) => {
+                let (_, mut cache) = init().await?;
+                let count = conditions::location::geodata::import(
+                    &mut cache,
+                    &input.file,
+                )
+                .await?;
+
+                format!("imported {count} postal codes")
+            }
+        },
+    };
+
+    Ok(result)
+}
+
+/// Fetches the current conditions for the requested region(s).
+async fn current(cmd: &CurrentCommand) -> eyre::Result<String> {
+    let (mut config, mut cache) = init().await?;
+    config.show_location |= cmd.show_location;
+    config.show_air_quality |= cmd.air_quality;
+    config.show_pollen |= cmd.pollen;
+    config.show_alerts |= cmd.alerts || cmd.format == CurrentFormat::Waybar;
+    config.show_astro |= cmd.astro;
+
+    if cmd.relocate {
+        config.location = None;
+        config.default_location = None;
+        config.ip_location_ttl_secs = 0;
+    }
+
+    if cmd.regions.len() > 1 || cmd.regions.first().is_some_and(|r| r == "-") {
+        if cmd.format == CurrentFormat::Waybar {
+            eyre::bail!("waybar output only supports a single region");
+        }
+
+        // clap can't make a conflict depend on the number of values
+        if cmd.pick.is_some() {
+            Conditions::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    "--pick only applies to a single region",
+                )
+                .exit();
+        }
+
+        return current_batch(config, &mut cache, &cmd.regions).await;
+    }
+
+    let region = cmd.coordinates.or_region(cmd.regions.first());
+    let output = with_pick(cmd.pick, async |pick| {
+        conditions::Conditions::new(config.clone(), region.clone(), pick)
+            .fetch(&mut cache)
+            .await
+    })
+    .await?;
+
+    match cmd.format {
+        CurrentFormat::Json => Ok(serde_json::to_string(&output)?),
+        CurrentFormat::Waybar => Ok(serde_json::to_string(
+            &conditions::waybar::Waybar::from(&output),
+        )?),
+    }
+}
+
+/// Fetches the current air quality for the requested region.
+async fn air(args: &RegionArgs) -> eyre::Result<String> {
+    let (config, mut cache) = init().await?;
+    let air_quality = with_pick(args.pick, async |pick| {
+        conditions::Conditions::new(config.clone(), args.region(), pick)
+            .air_quality(&mut cache)
+            .await
+    })
+    .await?;
+
+    Ok(serde_json::to_string(&air_quality)?)
+}
+
+/// Fetches the current pollen counts for the requested region.
+async fn pollen(args: &RegionArgs) -> eyre::Result<String> {
+    let (config, mut cache) = init().await?;
+    let pollen = with_pick(args.pick, async |pick| {
+        conditions::Conditions::new(config.clone(), args.region(), pick)
+            .pollen(&mut cache)
+            .await
+    })
+    .await?;
+
+    Ok(serde_json::to_string(&pollen)?)
+}
+
+/// Fetches the weather alerts active in the requested region.
+async fn alerts(args: &RegionArgs) -> eyre::Result<String> {
+    let (config, mut cache) = init().await?;
+    let alerts = with_pick(args.pick, async |pick| {
+        conditions::Conditions::new(config.clone(), args.region(), pick)
+            .alerts(&mut cache)
+            .await
+    })
+    .await?;
+
+    Ok(serde_json::to_string(&alerts)?)
+}
+
+/// Computes the position of the sun and moon in the requested region.
+async fn astro(args: &RegionArgs) -> eyre::Result<String> {
+    let (config, mut cache) = init().await?;
+    let astro = with_pick(args.pick, async |pick| {
+        conditions::Conditions::new(config.clone(), args.region(), pick)
+            .astro(&mut cache)
+            .await
+    })
+    .await?;
+
+    Ok(serde_json::to_string(&astro)?)
+}
+
+/// Evaluates the configured rules every interval, printing each event and
+/// running its hook.
+///
+/// Failing to evaluate the rules or to run a hook is only logged so that
+/// watching carries on.
+async fn watch(cmd: &WatchCommand) -> eyre::Result<String> {
+    let (config, mut cache) = init().await?;
+
+    if config.rules.is_empty() && config.webhooks.is_empty() {
+        eyre::bail!(
+            "no rules or webhooks configured, see `conditions config path`"
+        );
+    }
+
+    let region = cmd.coordinates.or_region(cmd.region.as_ref());
+    let interval = cmd.interval.unwrap_or(config.watch_interval_secs);
+    let conditions = conditions::Conditions::new(config.clone(), region, None);
+    let mut watcher = conditions::rules::Watcher::new(
+        config.rules.clone(),
+        config.hook.clone(),
+    );
+
+    loop {
+        match conditions.snapshot(&mut cache, &config.rules).await {
+            Ok(snapshot) => {
+                for event in watcher.check(&snapshot) {
+                    println!("{}", serde_json::to_string(&event)?);
+
+                    if let Err(err) = event.run_hook() {
+                        log::warn!("{err}");
+                    }
+
+                    for webhook in &config.webhooks {
+                        if let Err(err) = webhook.deliver(&config, &event) {
+                            log::warn!("webhook {} failed: {err}", webhook.url);
+                        }
+                    }
+                }
+            }
+            Err(err) if cmd.once => return Err(err.into()),
+            Err(err) => log::warn!("failed to evaluate rules: {err}"),
+        }
+
+        if cmd.once {
+            return Ok(String::new());
+        }
+
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+    }
+}
+
+/// Manages the stored and named locations.
+async fn location(command: &LocationSubcommand) -> eyre::Result<String> {
+    let result = match command {
+        LocationSubcommand::Set(input) => {
+            let (mut config, mut cache) = init().await?;
+            let region = input
+                .coordinates
+                .or_region(input.region.as_ref())
+                .unwrap_or_default();
+
+            with_pick(input.pick, async |pick| {
+                config.set_location(&mut cache, &region, pick).await
+            })
+            .await?
+        }
+        LocationSubcommand::View => {
+            let (config, mut cache) = init().await?;
+
+            let mut location = config.get_location(&mut cache).await?;
+
+            conditions::location::describe(&config, &mut cache, &mut location)
+                .await;
+
+            location.to_string()
+        }
+        LocationSubcommand::Unset => Config::unset_location()?,
+        LocationSubcommand::Add(input) => {
+            let (mut config, mut cache) = init().await?;
+            let region = input
+                .location
+                .coordinates
+                .or_region(input.location.region.as_ref())
+                .unwrap_or_default();
+
+            with_pick(input.location.pick, async |pick| {
+                config
+                    .add_location(&mut cache, &input.name, &region, pick)
+                    .await
+            })
+            .await?
+        }
+        LocationSubcommand::Remove(input) => {
+            Config::remove_location(&input.name)?
+        }
+        LocationSubcommand::List => Config::load()?.list_locations(),
+        LocationSubcommand::Default(input) => {
+            Config::set_default_location(&input.name)?
+        }
     };
 
     Ok(result)
 }
 
+/// Lists recorded observations or fetches records from the archive.
+async fn history(cmd: &HistoryCommand) -> eyre::Result<String> {
+    let (config, mut cache) = init().await?;
+
+    let Some(HistorySubcommand::Fetch(input)) = &cmd.command else {
+        let observations =
+            conditions::history::observations(&config, &mut cache, cmd.since)
+                .await?;
+
+        return Ok(match cmd.format {
+            HistoryFormat::Csv => conditions::history::to_csv(&observations),
+            HistoryFormat::Json => serde_json::to_string(&observations)?,
+        });
+    };
+
+    // ISO 8601 dates sort chronologically
+    if input.from > input.to {
+        Conditions::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                format!("--from {} is after --to {}", input.from, input.to),
+            )
+            .exit();
+    }
+
+    let location = match input.coordinates.or_region(input.region.as_ref()) {
+        Some(region) => {
+            conditions::location::get(&config, &mut cache, Some(&region), None)
+                .await?
+        }
+        None => config.get_location(&mut cache).await?,
+    };
+    let archive = conditions::history::backfill(
+        &config,
+        &mut cache,
+        &location,
+        &input.from,
+        &input.to,
+    )
+    .await?;
+
+    Ok(serde_json::to_string(&archive)?)
+}
+
+/// Inspects and cleans up the cache.
+async fn cache(command: &CacheSubcommand) -> eyre::Result<String> {
+    let result = match command {
+        CacheSubcommand::Path => Config::cache_path()?,
+        CacheSubcommand::List => {
+            let (_, mut cache) = init().await?;
+            let entries = cache.entries().await?;
+
+            if entries.is_empty() {
+                return Ok("no cached locations".to_string());
+            }
+
+            entries
+                .iter()
+                .map(|entry| {
+                    format!(
+                        "{:<24} {:>5}  {}",
+                        entry.key,
+                        format_age(entry.age_secs),
+                        entry
+                            .location
+                            .name
+                            .as_deref()
+                            .unwrap_or(&entry.location.loc),
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        CacheSubcommand::Clear => {
+            let (_, mut cache) = init().await?;
+
+            format!("removed {} cached locations", cache.clear().await?)
+        }
+        CacheSubcommand::Prune(input) => {
+            let (_, mut cache) = init().await?;
+            let mut removed = 0;
+
+            for key in &input.keys {
+                removed += cache.remove(key).await?;
+            }
+
+            if let Some(older_than) = input.older_than {
+                removed += cache.prune(older_than).await?;
+            }
+
+            format!("removed {removed} cached locations")
+        }
+        CacheSubcommand::Stats => {
+            let (_, mut cache) = init().await?;
+
+            cache.stats().await?.to_string()
+        }
+    };
+
+    Ok(result)
+}
+
+/// Formats an age in seconds using its largest unit, eg. `3h`.
+fn format_age(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{secs}s"),
+        60..=3_599 => format!("{}m", secs / 60),
+        3_600..=86_399 => format!("{}h", secs / 3_600),
+        _ => format!("{}d", secs / 86_400),
+    }
+}
+
+/// Fetches conditions for several regions, reading them from stdin when the
+/// only region is `-`.
+///
+/// Returns a JSON array with one entry per region, or the first error when
+/// every region failed.
+async fn current_batch(
+    config: Config,
+    cache: &mut Cache,
+    regions: &[String],
+) -> eyre::Result<String> {
+    let regions: Vec<String> = if regions == ["-"] {
+        read_regions(io::stdin().lock())?
+    } else {
+        regions.to_vec()
+    };
+
+    let mut results =
+        conditions::Conditions::fetch_all(config, regions.clone(), cache).await;
+
+    if !results.is_empty() && results.iter().all(Result::is_err) {
+        return Err(results.remove(0).unwrap_err().into());
+    }
+
+    let entries: Vec<BatchEntry> = regions
+        .into_iter()
+        .zip(results)
+        .map(|(region, result)| BatchEntry::new(region, result))
+        .collect();
+
+    Ok(serde_json::to_string(&entries)?)
+}
+
+/// Reads one region per line, skipping blank lines and `#` comments.
+fn read_regions(reader: impl BufRead) -> io::Result<Vec<String>> {
+    reader
+        .lines()
+        .map(|line| line.map(|line| line.trim().to_string()))
+        .filter(|line| {
+            line.as_ref()
+                .map_or(true, |line| !line.is_empty() && !line.starts_with('#'))
+        })
+        .collect()
+}
+
 async fn init() -> eyre::Result<(Config, Cache)> {
     let config = Config::load()?;
     let cache = Cache::new(Some(Config::cache_path()?)).await?;
 
     Ok((config, cache))
 }
+
+/// Runs `attempt` with `pick`, and again with the place chosen by the user
+/// for as long as it fails because the region is an ambiguous place name.
+async fn with_pick<T>(
+    mut pick: Option<usize>,
+    mut attempt: impl AsyncFnMut(Option<usize>) -> conditions::Result<T>,
+) -> eyre::Result<T> {
+    loop {
+        match attempt(pick).await {
+            Ok(value) => return Ok(value),
+            Err(err) => pick = Some(prompt_pick(err, pick)?),
+        }
+    }
+}
+
+/// Asks the user to choose between the places matching an ambiguous place
+/// name.
+///
+/// Only prompts when `err` is `AmbiguousLocation`, no pick was given and
+/// stdin is a terminal, otherwise `err` is returned as is.
+fn prompt_pick(
+    err: conditions::Error,
+    pick: Option<usize>,
+) -> eyre::Result<usize> {
+    let conditions::Error::AmbiguousLocation { query, candidates } = &err
+    else {
+        return Err(err.into());
+    };
+
+    if pick.is_some() || !io::stdin().is_terminal() {
+        return Err(err.into());
+    }
+
+    eprintln!("\"{query}\" matches several places:");
+
+    for (i, candidate) in candidates.iter().enumerate() {
+        eprintln!("  {}) {candidate}", i + 1);
+    }
+
+    eprint!("pick one [1-{}]: ", candidates.len());
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+
+    match answer.trim().parse::<usize>() {
+        Ok(pick) if (1..=candidates.len()).contains(&pick) => Ok(pick),
+        _ => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn it_attempts_with_pick() {
+        let mut picks = vec![];
+
+        let result = with_pick(Some(2), async |pick| {
+            picks.push(pick);
+
+            Ok(pick)
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), Some(2));
+        assert_eq!(picks, [Some(2)]);
+
+        // without a terminal to prompt on, ambiguity is an error
+        let mut attempts = 0;
+        let result: eyre::Result<()> = with_pick(None, async |_| {
+            attempts += 1;
+
+            Err(conditions::Error::AmbiguousLocation {
+                query: "Paris".to_string(),
+                candidates: vec!["Paris, FR".to_string()],
+            })
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn it_formats_ages() {
+        assert_eq!(format_age(0), "0s");
+        assert_eq!(format_age(59), "59s");
+        assert_eq!(format_age(60), "1m");
+        assert_eq!(format_age(3_599), "59m");
+        assert_eq!(format_age(3_600), "1h");
+        assert_eq!(format_age(86_400), "1d");
+        assert_eq!(format_age(u64::MAX), "213503982334601d");
+    }
+
+    #[test]
+    fn it_reads_regions_from_lines() {
+        let input = "10001,US\n\n  # office\n  Lisbon, PT  \n@home";
+
+        assert_eq!(
+            read_regions(input.as_bytes()).unwrap(),
+            ["10001,US", "Lisbon, PT", "@home"]
+        );
+        assert!(read_regions("".as_bytes()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_rejects_coordinates_with_regions() {
+        for args in [
+            ["conditions", "current", "-", "--lat", "1", "--lon", "2"]
+                .as_slice(),
+            &[
+                "conditions",
+                "current",
+                "@home",
+                "@office",
+                "--lat",
+                "1",
+                "--lon",
+                "2",
+            ],
+        ] {
+            assert_eq!(
+                Conditions::try_parse_from(args).unwrap_err().kind(),
+                ErrorKind::ArgumentConflict
+            );
+        }
+    }
+
+    #[test]
+    fn it_rejects_invalid_history_dates() {
+        let args = [
+            "conditions",
+            "history",
+            "fetch",
+            "--from",
+            "2024-02-30",
+            "--to",
+            "2024-03-01",
+        ];
+
+        assert_eq!(
+            Conditions::try_parse_from(args).unwrap_err().kind(),
+            ErrorKind::ValueValidation
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use serde::Serialize;
+
+use crate::{
+    api::Fetchable, config::Config, location::Location, Error, Result,
+};
+
+mod open_meteo;
+mod pollen;
+pub(crate) mod weather_api;
+
+pub use pollen::{Pollen, Severity};
+
+/// Current air quality at a location.
+///
+/// Concentrations are in μg/m³, values are `None` when the provider has no
+/// data for the location.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct AirQuality {
+    /// US EPA Air Quality Index, 0-500.
+    pub us_aqi: Option<u32>,
+    /// European Air Quality Index, 0-100 (values above 100 are extremely
+    /// poor).
+    pub european_aqi: Option<u32>,
+    pub pm2_5: Option<f64>,
+    pub pm10: Option<f64>,
+    pub ozone: Option<f64>,
+    pub nitrogen_dioxide: Option<f64>,
+    /// The provider that reported the air quality.
+    pub provider: &'static str,
+}
+
+impl AirQuality {
+    /// Fetches the air quality for `location`, trying `WeatherAPI` (when a
+    /// token is configured) and then Open-Meteo.
+    ///
+    /// # Errors
+    ///
+    /// `Error::AllProvidersFailed` when neither provider succeeded.
+    pub fn get(config: &Config, location: &Location) -> Result<Self> {
+        let mut errors = vec![];
+
+        // without a token WeatherAPI isn't tried, let alone failed
+        let weather_api = weather_api::Client::new(config, location);
+        if weather_api.is_valid() {
+            match weather_api.fetch() {
+                Ok(air_quality) => return Ok(air_quality),
+                Err(err) => errors.push(err),
+            }
+        }
+
+        match open_meteo::Client::new(config, location).fetch() {
+            Ok(air_quality) => return Ok(air_quality),
+            Err(err) => errors.push(err),
+        }
+
+        Err(Error::AllProvidersFailed(errors))
+    }
+
+    /// Builds an `AirQuality` from concentrations alone, computing both
+    /// indices from them.
+    ///
+    /// The US AQI is the highest of the PM2.5, PM10 and NO2 sub-indices
+    /// (ozone is left out, its EPA breakpoints apply to 8 hour averages in
+    /// ppm) and the European AQI the highest of all four.
+    fn from_concentrations(
+        pm2_5: Option<f64>,
+        pm10: Option<f64>,
+        ozone: Option<f64>,
+        nitrogen_dioxide: Option<f64>,
+        provider: &'static str,
+    ) -> Self {
+        let us_aqi = [
+            pm2_5.map(|c| index(&US_PM2_5, &US_SCALE, c)),
+            pm10.map(|c| index(&US_PM10, &US_SCALE, c)),
+            // μg/m³ to ppb at 25°C
+            nitrogen_dioxide.map(|c| index(&US_NO2, &US_SCALE, c / 1.88)),
+        ]
+        .into_iter()
+        .flatten()
+        .max();
+        let european_aqi = [
+            pm2_5.map(|c| index(&EU_PM2_5, &EU_SCALE, c)),
+            pm10.map(|c| index(&EU_PM10, &EU_SCALE, c)),
+            ozone.map(|c| index(&EU_OZONE, &EU_SCALE, c)),
+            nitrogen_dioxide.map(|c| index(&EU_NO2, &EU_SCALE, c)),
+        ]
+        .into_iter()
+        .flatten()
+        .max();
+
+        Self {
+            us_aqi,
+            european_aqi,
+            pm2_5,
+            pm10,
+            ozone,
+            nitrogen_dioxide,
+            provider,
+        }
+    }
+}
+
+// Breakpoints are the concentrations bounding each band of the matching
+// scale, eg. PM2.5 from 9.0 to 35.4 μg/m³ maps linearly onto a US AQI of
+// 50 to 100. Concentrations beyond the last breakpoint get the top of the
+// scale.
+//
+// https://www.airnow.gov/sites/default/files/2020-05/aqi-technical-assistance-document-sept2018.pdf
+// (with the 2024 PM2.5 revision) and
+// https://open-meteo.com/en/docs/air-quality-api#european_aqi
+
+const US_SCALE: [f64; 7] = [0.0, 50.0, 100.0, 150.0, 200.0, 300.0, 500.0];
+const US_PM2_5: [f64; 7] = [0.0, 9.0, 35.4, 55.4, 125.4, 225.4, 325.4];
+const US_PM10: [f64; 7] = [0.0, 54.0, 154.0, 254.0, 354.0, 424.0, 604.0];
+/// 1 hour average, in ppb.
+const US_NO2: [f64; 7] = [0.0, 53.0, 100.0, 360.0, 649.0, 1_249.0, 2_049.0];
+
+const EU_SCALE: [f64; 7] = [0.0, 20.0, 40.0, 60.0, 80.0, 100.0, 120.0];
+const EU_PM2_5: [f64; 7] = [0.0, 10.0, 20.0, 25.0, 50.0, 75.0, 800.0];
+const EU_PM10: [f64; 7] = [0.0, 20.0, 40.0, 50.0, 100.0, 150.0, 1_200.0];
+const EU_OZONE: [f64; 7] = [0.0, 50.0, 100.0, 130.0, 240.0, 380.0, 800.0];
+const EU_NO2: [f64; 7] = [0.0, 40.0, 90.0, 120.0, 230.0, 340.0, 1_000.0];
+
+/// Interpolates `concentration` between the `breakpoints` onto `scale`.
+fn index(breakpoints: &[f64; 7], scale: &[f64; 7], concentration: f64) -> u32 {
+    let concentration = concentration.max(0.0);
+    let band = breakpoints
+        .windows(2)
+        .position(|bounds| concentration <= bounds[1]);
+
+    let value = match band {
+        Some(i) => {
+            let (low, high) = (breakpoints[i], breakpoints[i + 1]);
+
+            scale[i]
+                + (scale[i + 1] - scale[i]) * (concentration - low)
+                    / (high - low)
+        }
+        None => scale[6],
+    };
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let value = value.round() as u32;
+
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_interpolates_index() {
+        assert_eq!(index(&US_PM2_5, &US_SCALE, 0.0), 0);
+        assert_eq!(index(&US_PM2_5, &US_SCALE, 9.0), 50);
+        assert_eq!(index(&US_PM2_5, &US_SCALE, 35.4), 100);
+        assert_eq!(index(&US_PM2_5, &US_SCALE, 45.4), 125);
+        assert_eq!(index(&US_PM2_5, &US_SCALE, 1_000.0), 500);
+        assert_eq!(index(&EU_OZONE, &EU_SCALE, 75.0), 30);
+        assert_eq!(index(&EU_NO2, &EU_SCALE, -1.0), 0);
+    }
+
+    #[test]
+    fn it_computes_indices_from_concentrations() {
+        let air_quality = AirQuality::from_concentrations(
+            Some(45.4),
+            Some(54.0),
+            Some(75.0),
+            None,
+            "test",
+        );
+
+        assert_eq!(air_quality.us_aqi, Some(125));
+        assert_eq!(air_quality.european_aqi, Some(76));
+
+        let air_quality =
+            AirQuality::from_concentrations(None, None, None, None, "test");
+
+        assert_eq!(air_quality.us_aqi, None);
+        assert_eq!(air_quality.european_aqi, None);
+    }
+}
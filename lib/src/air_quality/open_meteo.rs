@@ -0,0 +1,158 @@
+use serde::Deserialize;
+
+use super::AirQuality;
+use crate::{config::RequestPolicy, location::Location, Config};
+
+pub(super) const PROVIDER: &str = "open_meteo_air_quality";
+
+// https://air-quality-api.open-meteo.com/v1/air-quality?latitude=35.15&longitude=-80.81&current=us_aqi,european_aqi,pm2_5,pm10,ozone,nitrogen_dioxide
+// {
+//   "latitude": 35.1,
+//   "longitude": -80.8,
+//   "current": {
+//     "time": "2024-06-01T14:00",
+//     "interval": 3600,
+//     "us_aqi": 42,
+//     "european_aqi": 21,
+//     "pm2_5": 9.8,
+//     "pm10": 10.3,
+//     "ozone": 84.0,
+//     "nitrogen_dioxide": 3.1
+//   }
+// }
+//
+// values are `null` outside of the model's coverage
+
+const CURRENT: &str = "us_aqi,european_aqi,pm2_5,pm10,ozone,nitrogen_dioxide";
+
+pub struct Client {
+    policy: RequestPolicy,
+    query: Vec<(String, String)>,
+}
+
+impl Client {
+    pub fn new(config: &Config, location: &Location) -> Self {
+        Self {
+            policy: config.network.policy(PROVIDER),
+            query: vec![
+                ("latitude".to_string(), location.latitude.clone()),
+                ("longitude".to_string(), location.longitude.clone()),
+                ("current".to_string(), CURRENT.to_string()),
+            ],
+        }
+    }
+}
+
+impl crate::api::Fetchable<Response, AirQuality> for Client {
+    fn provider(&self) -> &'static str {
+        PROVIDER
+    }
+
+    fn url(&self) -> &'static str {
+        "https://air-quality-api.open-meteo.com/v1/air-quality"
+    }
+
+    fn policy(&self) -> RequestPolicy {
+        self.policy
+    }
+
+    fn query(&self) -> Option<&Vec<(String, String)>> {
+        Some(&self.query)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Current {
+    us_aqi: Option<f64>,
+    european_aqi: Option<f64>,
+    pm2_5: Option<f64>,
+    pm10: Option<f64>,
+    ozone: Option<f64>,
+    nitrogen_dioxide: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Response {
+    current: Current,
+}
+
+impl From<Response> for AirQuality {
+    fn from(response: Response) -> Self {
+        let current = response.current;
+        let mut air_quality = AirQuality::from_concentrations(
+            current.pm2_5,
+            current.pm10,
+            current.ozone,
+            current.nitrogen_dioxide,
+            PROVIDER,
+        );
+
+        // the reported indices account for averaging periods the computed
+        // ones can't, prefer them
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let round = |aqi: f64| aqi.max(0.0).round() as u32;
+
+        if let Some(us_aqi) = current.us_aqi {
+            air_quality.us_aqi = Some(round(us_aqi));
+        }
+
+        if let Some(european_aqi) = current.european_aqi {
+            air_quality.european_aqi = Some(round(european_aqi));
+        }
+
+        air_quality
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_builds_query() {
+        let location = Location {
+            latitude: "35.15".to_string(),
+            longitude: "-80.81".to_string(),
+            ..Default::default()
+        };
+        let client = Client::new(&Config::default(), &location);
+
+        assert_eq!(
+            client.query,
+            vec![
+                ("latitude".to_string(), "35.15".to_string()),
+                ("longitude".to_string(), "-80.81".to_string()),
+                ("current".to_string(), CURRENT.to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_converts_response() {
+        let response: Response = serde_json::from_str(
+            r#"{
+                "current": {
+                    "time": "2024-06-01T14:00",
+                    "interval": 3600,
+                    "us_aqi": 42,
+                    "european_aqi": null,
+                    "pm2_5": 9.8,
+                    "pm10": 10.3,
+                    "ozone": 84.0,
+                    "nitrogen_dioxide": null
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let air_quality = AirQuality::from(response);
+
+        assert_eq!(air_quality.us_aqi, Some(42));
+        // computed from the concentrations when not reported
+        assert_eq!(air_quality.european_aqi, Some(34));
+        assert_eq!(air_quality.pm2_5, Some(9.8));
+        assert_eq!(air_quality.nitrogen_dioxide, None);
+        assert_eq!(air_quality.provider, PROVIDER);
+    }
+}
@@ -0,0 +1,270 @@
+use serde::{Deserialize, Serialize};
+
+use super::open_meteo::PROVIDER;
+use crate::{
+    api::Fetchable, config::RequestPolicy, location::Location, Config, Result,
+};
+
+// https://air-quality-api.open-meteo.com/v1/air-quality?latitude=48.85&longitude=2.35&current=alder_pollen,birch_pollen,grass_pollen,mugwort_pollen,olive_pollen,ragweed_pollen
+// {
+//   "latitude": 48.85,
+//   "longitude": 2.35,
+//   "current": {
+//     "time": "2024-04-15T14:00",
+//     "interval": 3600,
+//     "alder_pollen": 0.1,
+//     "birch_pollen": 112.4,
+//     "grass_pollen": 3.0,
+//     "mugwort_pollen": 0.0,
+//     "olive_pollen": 0.0,
+//     "ragweed_pollen": 0.0
+//   }
+// }
+//
+// pollen is only forecast for Europe, values are `null` elsewhere
+
+const CURRENT: &str = "alder_pollen,birch_pollen,grass_pollen,mugwort_pollen,olive_pollen,ragweed_pollen";
+
+/// How bad the pollen count is, from the National Allergy Bureau scale.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    None,
+    Low,
+    Moderate,
+    High,
+    VeryHigh,
+}
+
+impl Severity {
+    /// Bands `count` (grains/m³) using the lower bounds of the low, moderate,
+    /// high and very high bands.
+    fn of(count: f64, bounds: [f64; 4]) -> Self {
+        match bounds.iter().filter(|bound| count >= **bound).count() {
+            0 => Self::None,
+            1 => Self::Low,
+            2 => Self::Moderate,
+            3 => Self::High,
+            _ => Self::VeryHigh,
+        }
+    }
+
+    /// Nerd Font icon for the band.
+    #[must_use]
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::None => "󰧰", // flower-outline
+            Self::Low => "󰶌",  // flower-pollen-outline
+            Self::Moderate | Self::High | Self::VeryHigh => "󰢛", // flower-pollen
+        }
+    }
+}
+
+const TREES: [f64; 4] = [1.0, 15.0, 90.0, 1_500.0];
+const GRASS: [f64; 4] = [1.0, 5.0, 20.0, 200.0];
+const WEEDS: [f64; 4] = [1.0, 10.0, 50.0, 500.0];
+
+/// Current pollen counts at a location, in grains/m³.
+///
+/// Counts are `None` outside of Europe, the only region Open-Meteo forecasts
+/// pollen for, and so are `severity` and `icon` when every count is.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Pollen {
+    pub alder: Option<f64>,
+    pub birch: Option<f64>,
+    pub grass: Option<f64>,
+    pub mugwort: Option<f64>,
+    pub olive: Option<f64>,
+    pub ragweed: Option<f64>,
+    /// The worst band among the counts.
+    pub severity: Option<Severity>,
+    /// Icon matching `severity`.
+    pub icon: Option<String>,
+}
+
+impl Pollen {
+    /// Fetches the pollen counts for `location` from Open-Meteo.
+    ///
+    /// # Errors
+    ///
+    /// Any error fetching the counts.
+    pub fn get(config: &Config, location: &Location) -> Result<Self> {
+        Client::new(config, location).fetch()
+    }
+}
+
+pub struct Client {
+    policy: RequestPolicy,
+    query: Vec<(String, String)>,
+}
+
+impl Client {
+    pub fn new(config: &Config, location: &Location) -> Self {
+        Self {
+            policy: config.network.policy(PROVIDER),
+            query: vec![
+                ("latitude".to_string(), location.latitude.clone()),
+                ("longitude".to_string(), location.longitude.clone()),
+                ("current".to_string(), CURRENT.to_string()),
+            ],
+        }
+    }
+}
+
+impl Fetchable<Response, Pollen> for Client {
+    fn provider(&self) -> &'static str {
+        PROVIDER
+    }
+
+    fn url(&self) -> &'static str {
+        "https://air-quality-api.open-meteo.com/v1/air-quality"
+    }
+
+    fn policy(&self) -> RequestPolicy {
+        self.policy
+    }
+
+    fn query(&self) -> Option<&Vec<(String, String)>> {
+        Some(&self.query)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Current {
+    #[serde(rename = "alder_pollen")]
+    alder: Option<f64>,
+    #[serde(rename = "birch_pollen")]
+    birch: Option<f64>,
+    #[serde(rename = "grass_pollen")]
+    grass: Option<f64>,
+    #[serde(rename = "mugwort_pollen")]
+    mugwort: Option<f64>,
+    #[serde(rename = "olive_pollen")]
+    olive: Option<f64>,
+    #[serde(rename = "ragweed_pollen")]
+    ragweed: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Response {
+    current: Current,
+}
+
+impl From<Response> for Pollen {
+    fn from(response: Response) -> Self {
+        let current = response.current;
+        let severity = [
+            (current.alder, TREES),
+            (current.birch, TREES),
+            (current.olive, TREES),
+            (current.grass, GRASS),
+            (current.mugwort, WEEDS),
+            (current.ragweed, WEEDS),
+        ]
+        .into_iter()
+        .filter_map(|(count, bounds)| Some(Severity::of(count?, bounds)))
+        .max();
+
+        Self {
+            alder: current.alder,
+            birch: current.birch,
+            grass: current.grass,
+            mugwort: current.mugwort,
+            olive: current.olive,
+            ragweed: current.ragweed,
+            severity,
+            icon: severity.map(|severity| severity.icon().to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_bands_counts() {
+        assert_eq!(Severity::of(0.0, TREES), Severity::None);
+        assert_eq!(Severity::of(1.0, TREES), Severity::Low);
+        assert_eq!(Severity::of(14.9, TREES), Severity::Low);
+        assert_eq!(Severity::of(15.0, TREES), Severity::Moderate);
+        assert_eq!(Severity::of(20.0, GRASS), Severity::High);
+        assert_eq!(Severity::of(500.0, WEEDS), Severity::VeryHigh);
+    }
+
+    #[test]
+    fn it_converts_response() {
+        let response: Response = serde_json::from_str(
+            r#"{
+                "current": {
+                    "time": "2024-04-15T14:00",
+                    "interval": 3600,
+                    "alder_pollen": 0.1,
+                    "birch_pollen": 112.4,
+                    "grass_pollen": 3.0,
+                    "mugwort_pollen": 0.0,
+                    "olive_pollen": null,
+                    "ragweed_pollen": 0.0
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let pollen = Pollen::from(response);
+
+        assert_eq!(pollen.birch, Some(112.4));
+        assert_eq!(pollen.olive, None);
+        assert_eq!(pollen.severity, Some(Severity::High));
+        assert_eq!(pollen.icon.as_deref(), Some(Severity::High.icon()));
+    }
+
+    #[test]
+    fn it_has_no_severity_without_counts() {
+        let response: Response =
+            serde_json::from_str(r#"{"current": {"alder_pollen": null}}"#)
+                .unwrap();
+
+        let pollen = Pollen::from(response);
+
+        assert_eq!(pollen.severity, None);
+        assert_eq!(pollen.icon, None);
+    }
+
+    #[test]
+    fn it_has_no_severity_outside_europe() {
+        let response: Response = serde_json::from_str(
+            r#"{
+                "current": {
+                    "time": "2024-04-15T14:00",
+                    "interval": 3600,
+                    "alder_pollen": null,
+                    "birch_pollen": null,
+                    "grass_pollen": null,
+                    "mugwort_pollen": null,
+                    "olive_pollen": null,
+                    "ragweed_pollen": null
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let pollen = Pollen::from(response);
+
+        assert_eq!(pollen, Pollen::default());
+        assert_eq!(pollen.severity, None);
+        assert_eq!(pollen.icon, None);
+    }
+
+    #[test]
+    fn it_reports_none_severity_for_zero_counts() {
+        let response: Response =
+            serde_json::from_str(r#"{"current": {"grass_pollen": 0.0}}"#)
+                .unwrap();
+
+        let pollen = Pollen::from(response);
+
+        assert_eq!(pollen.severity, Some(Severity::None));
+        assert_eq!(pollen.icon.as_deref(), Some(Severity::None.icon()));
+    }
+}
@@ -0,0 +1,170 @@
+use serde::Deserialize;
+
+use super::AirQuality;
+use crate::{
+    config::{Config, RequestPolicy},
+    location::Location,
+};
+
+const PROVIDER: &str = "weatherapi";
+
+pub struct Client {
+    is_valid: bool,
+    policy: RequestPolicy,
+    query: Vec<(String, String)>,
+}
+
+impl Client {
+    pub fn new(config: &Config, location: &Location) -> Self {
+        let key = config.weatherapi_token.clone();
+        let is_valid = key.is_some();
+
+        Self {
+            is_valid,
+            policy: config.network.policy(PROVIDER),
+            query: vec![
+                ("key".to_string(), key.unwrap_or_default()),
+                ("q".to_string(), location.loc.clone()),
+                ("aqi".to_string(), "yes".to_string()),
+            ],
+        }
+    }
+}
+
+impl crate::api::Fetchable<Response, AirQuality> for Client {
+    fn provider(&self) -> &'static str {
+        PROVIDER
+    }
+
+    fn url(&self) -> &'static str {
+        "http://api.weatherapi.com/v1/current.json"
+    }
+
+    fn is_valid(&self) -> bool {
+        self.is_valid
+    }
+
+    fn policy(&self) -> RequestPolicy {
+        self.policy
+    }
+
+    fn query(&self) -> Option<&Vec<(String, String)>> {
+        Some(&self.query)
+    }
+}
+
+/// Response represented as JSON response, concentrations are in μg/m³ and
+/// `us-epa-index` is a 1-6 band rather than an AQI value
+/// ```json
+/// {
+///   "current": {
+///     "air_quality": {
+///       "co": f64,
+///       "no2": f64,
+///       "o3": f64,
+///       "so2": f64,
+///       "pm2_5": f64,
+///       "pm10": f64,
+///       "us-epa-index": u8,
+///       "gb-defra-index": u8
+///     }
+///   }
+/// }
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct Response {
+    current: WeatherAPIResultCurrent,
+}
+
+#[derive(Debug, Deserialize)]
+struct WeatherAPIResultCurrent {
+    #[serde(default)]
+    air_quality: WeatherAPIResultAirQuality,
+}
+
+/// The `air_quality` object, also part of the conditions response when it
+/// was requested with `aqi=yes`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct WeatherAPIResultAirQuality {
+    no2: Option<f64>,
+    o3: Option<f64>,
+    pm2_5: Option<f64>,
+    pm10: Option<f64>,
+}
+
+impl From<Response> for AirQuality {
+    fn from(result: Response) -> Self {
+        AirQuality::from(result.current.air_quality)
+    }
+}
+
+impl From<WeatherAPIResultAirQuality> for AirQuality {
+    fn from(air_quality: WeatherAPIResultAirQuality) -> Self {
+        AirQuality::from_concentrations(
+            air_quality.pm2_5,
+            air_quality.pm10,
+            air_quality.o3,
+            air_quality.no2,
+            PROVIDER,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_creates_client_with_query() {
+        let config = Config {
+            weatherapi_token: Some("token123".to_string()),
+            ..Default::default()
+        };
+        let location = Location {
+            loc: "loc_string".to_string(),
+            ..Default::default()
+        };
+        let client = Client::new(&config, &location);
+
+        assert!(client.is_valid);
+        assert_eq!(
+            client.query,
+            vec![
+                ("key".to_string(), "token123".to_string()),
+                ("q".to_string(), "loc_string".to_string()),
+                ("aqi".to_string(), "yes".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_converts_response() {
+        let response: Response = serde_json::from_str(
+            r#"{
+                "current": {
+                    "temp_f": 71.1,
+                    "air_quality": {
+                        "co": 223.6,
+                        "no2": 3.9,
+                        "o3": 64.4,
+                        "so2": 1.2,
+                        "pm2_5": 45.4,
+                        "pm10": 54.0,
+                        "us-epa-index": 3,
+                        "gb-defra-index": 5
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let air_quality = AirQuality::from(response);
+
+        assert_eq!(air_quality.us_aqi, Some(125));
+        assert_eq!(air_quality.european_aqi, Some(76));
+        assert_eq!(air_quality.ozone, Some(64.4));
+        assert_eq!(air_quality.nitrogen_dioxide, Some(3.9));
+        assert_eq!(air_quality.provider, PROVIDER);
+    }
+}
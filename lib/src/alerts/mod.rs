@@ -0,0 +1,121 @@
+use std::{cmp::Reverse, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::Fetchable, config::Config, location::Location, Error, Result,
+};
+
+mod nws;
+mod weather_api;
+
+/// CAP severity of an alert, ordered from least to most severe.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Deserialize,
+    Serialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    #[default]
+    Unknown,
+    Minor,
+    Moderate,
+    Severe,
+    Extreme,
+}
+
+impl From<&str> for Severity {
+    fn from(severity: &str) -> Self {
+        match severity.to_lowercase().as_str() {
+            "minor" => Self::Minor,
+            "moderate" => Self::Moderate,
+            "severe" => Self::Severe,
+            "extreme" => Self::Extreme,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Self::Unknown => "unknown",
+            Self::Minor => "minor",
+            Self::Moderate => "moderate",
+            Self::Severe => "severe",
+            Self::Extreme => "extreme",
+        };
+        write!(f, "{text}")
+    }
+}
+
+/// A weather alert active at a location.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Alert {
+    /// Kind of alert, eg. `Tornado Warning`.
+    pub event: String,
+    pub headline: String,
+    pub severity: Severity,
+    /// When the alert takes effect, as reported by the provider (ISO 8601).
+    pub effective: Option<String>,
+    /// When the alert expires, as reported by the provider (ISO 8601).
+    pub expires: Option<String>,
+}
+
+/// Fetches the alerts active at `location`, trying `WeatherAPI` (when a token
+/// is configured) and then the US National Weather Service.
+///
+/// Alerts are sorted from most to least severe.
+///
+/// # Errors
+///
+/// `Error::AllProvidersFailed` when neither provider succeeded, the National
+/// Weather Service only covers the US.
+pub fn get(config: &Config, location: &Location) -> Result<Vec<Alert>> {
+    let mut errors = vec![];
+
+    match weather_api::Client::new(config, location).fetch() {
+        Ok(alerts) => return Ok(by_severity(alerts)),
+        Err(err) => errors.push(err),
+    }
+
+    match nws::Client::new(config, location).fetch() {
+        Ok(alerts) => return Ok(by_severity(alerts)),
+        Err(err) => errors.push(err),
+    }
+
+    Err(Error::AllProvidersFailed(errors))
+}
+
+fn by_severity(mut alerts: Vec<Alert>) -> Vec<Alert> {
+    alerts.sort_by_key(|alert| Reverse(alert.severity));
+
+    alerts
+}
+
+/// Turns an empty string, as providers report missing values, into `None`.
+fn non_empty(value: String) -> Option<String> {
+    (!value.is_empty()).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_severity() {
+        assert_eq!(Severity::from("Extreme"), Severity::Extreme);
+        assert_eq!(Severity::from("moderate"), Severity::Moderate);
+        assert_eq!(Severity::from(""), Severity::Unknown);
+        assert!(Severity::Severe > Severity::Moderate);
+        assert!(Severity::Minor > Severity::Unknown);
+    }
+}
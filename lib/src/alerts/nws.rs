@@ -0,0 +1,175 @@
+use serde::Deserialize;
+
+use super::{Alert, Severity};
+use crate::{
+    config::{Config, RequestPolicy},
+    location::Location,
+};
+
+const PROVIDER: &str = "nws";
+
+// https://api.weather.gov/alerts/active?point=35.15,-80.81
+// {
+//   "type": "FeatureCollection",
+//   "features": [
+//     {
+//       "properties": {
+//         "event": "Tornado Warning",
+//         "headline": "Tornado Warning issued June 1 at 2:05PM EDT until June 1 at 2:45PM EDT by NWS Charlotte",
+//         "severity": "Extreme",
+//         "effective": "2024-06-01T14:05:00-04:00",
+//         "expires": "2024-06-01T14:45:00-04:00",
+//         ...
+//       }
+//     }
+//   ]
+// }
+//
+// only points within the US are covered, `headline` and `expires` can be
+// `null`
+
+pub struct Client {
+    policy: RequestPolicy,
+    query: Vec<(String, String)>,
+}
+
+impl Client {
+    pub fn new(config: &Config, location: &Location) -> Self {
+        Self {
+            policy: config.network.policy(PROVIDER),
+            query: vec![("point".to_string(), point(location))],
+        }
+    }
+}
+
+/// Formats the location as the `lat,lon` point the API expects, which
+/// rejects more than four decimals.
+fn point(location: &Location) -> String {
+    let round = |coordinate: &str| {
+        coordinate.parse::<f64>().map_or_else(
+            |_| coordinate.to_string(),
+            |coordinate| {
+                format!("{coordinate:.4}")
+                    .trim_end_matches('0')
+                    .trim_end_matches('.')
+                    .to_string()
+            },
+        )
+    };
+
+    format!(
+        "{},{}",
+        round(&location.latitude),
+        round(&location.longitude)
+    )
+}
+
+impl crate::api::Fetchable<Response, Vec<Alert>> for Client {
+    fn provider(&self) -> &'static str {
+        PROVIDER
+    }
+
+    fn url(&self) -> &'static str {
+        "https://api.weather.gov/alerts/active"
+    }
+
+    fn policy(&self) -> RequestPolicy {
+        self.policy
+    }
+
+    fn query(&self) -> Option<&Vec<(String, String)>> {
+        Some(&self.query)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Response {
+    features: Vec<Feature>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Feature {
+    properties: Properties,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Properties {
+    event: String,
+    headline: Option<String>,
+    severity: String,
+    effective: Option<String>,
+    expires: Option<String>,
+}
+
+impl From<Response> for Vec<Alert> {
+    fn from(response: Response) -> Self {
+        response
+            .features
+            .into_iter()
+            .map(|feature| {
+                let properties = feature.properties;
+
+                Alert {
+                    severity: Severity::from(properties.severity.as_str()),
+                    headline: properties
+                        .headline
+                        .unwrap_or_else(|| properties.event.clone()),
+                    event: properties.event,
+                    effective: properties.effective,
+                    expires: properties.expires,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_rounds_point() {
+        let location = Location {
+            latitude: "35.159126".to_string(),
+            longitude: "-80.8".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(point(&location), "35.1591,-80.8");
+    }
+
+    #[test]
+    fn it_converts_response() {
+        let response: Response = serde_json::from_str(
+            r#"{
+                "type": "FeatureCollection",
+                "features": [
+                    {
+                        "properties": {
+                            "event": "Heat Advisory",
+                            "headline": null,
+                            "severity": "Moderate",
+                            "effective": "2024-06-01T11:00:00-04:00",
+                            "expires": null
+                        }
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let alerts = Vec::<Alert>::from(response);
+
+        assert_eq!(
+            alerts,
+            vec![Alert {
+                event: "Heat Advisory".to_string(),
+                headline: "Heat Advisory".to_string(),
+                severity: Severity::Moderate,
+                effective: Some("2024-06-01T11:00:00-04:00".to_string()),
+                expires: None,
+            }]
+        );
+    }
+}
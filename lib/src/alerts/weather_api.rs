@@ -0,0 +1,177 @@
+use serde::Deserialize;
+
+use super::{non_empty, Alert, Severity};
+use crate::{
+    config::{Config, RequestPolicy},
+    location::Location,
+};
+
+const PROVIDER: &str = "weatherapi";
+
+pub struct Client {
+    is_valid: bool,
+    policy: RequestPolicy,
+    query: Vec<(String, String)>,
+}
+
+impl Client {
+    pub fn new(config: &Config, location: &Location) -> Self {
+        let key = config.weatherapi_token.clone();
+        let is_valid = key.is_some();
+
+        Self {
+            is_valid,
+            policy: config.network.policy(PROVIDER),
+            query: vec![
+                ("key".to_string(), key.unwrap_or_default()),
+                ("q".to_string(), location.loc.clone()),
+                ("days".to_string(), "1".to_string()),
+                ("alerts".to_string(), "yes".to_string()),
+            ],
+        }
+    }
+}
+
+impl crate::api::Fetchable<Response, Vec<Alert>> for Client {
+    fn provider(&self) -> &'static str {
+        PROVIDER
+    }
+
+    fn url(&self) -> &'static str {
+        "http://api.weatherapi.com/v1/forecast.json"
+    }
+
+    fn is_valid(&self) -> bool {
+        self.is_valid
+    }
+
+    fn policy(&self) -> RequestPolicy {
+        self.policy
+    }
+
+    fn query(&self) -> Option<&Vec<(String, String)>> {
+        Some(&self.query)
+    }
+}
+
+/// Response represented as JSON response, alerts are only returned by the
+/// forecast endpoint and fields are empty strings when missing
+/// ```json
+/// {
+///   "alerts": {
+///     "alert": [
+///       {
+///         "headline": String,
+///         "severity": String,
+///         "event": String,
+///         "effective": String,
+///         "expires": String
+///       }
+///     ]
+///   }
+/// }
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct Response {
+    #[serde(default)]
+    alerts: WeatherAPIResultAlerts,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct WeatherAPIResultAlerts {
+    #[serde(default)]
+    alert: Vec<WeatherAPIResultAlert>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct WeatherAPIResultAlert {
+    headline: String,
+    severity: String,
+    event: String,
+    effective: String,
+    expires: String,
+}
+
+impl From<Response> for Vec<Alert> {
+    fn from(result: Response) -> Self {
+        result
+            .alerts
+            .alert
+            .into_iter()
+            .map(|alert| Alert {
+                severity: Severity::from(alert.severity.as_str()),
+                event: alert.event,
+                headline: alert.headline,
+                effective: non_empty(alert.effective),
+                expires: non_empty(alert.expires),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_creates_client_with_query() {
+        let config = Config {
+            weatherapi_token: Some("token123".to_string()),
+            ..Default::default()
+        };
+        let location = Location {
+            loc: "loc_string".to_string(),
+            ..Default::default()
+        };
+        let client = Client::new(&config, &location);
+
+        assert!(client.is_valid);
+        assert!(client
+            .query
+            .contains(&("alerts".to_string(), "yes".to_string())));
+    }
+
+    #[test]
+    fn it_converts_response() {
+        let response: Response = serde_json::from_str(
+            r#"{
+                "current": {"temp_f": 71.1},
+                "alerts": {
+                    "alert": [
+                        {
+                            "headline": "Tornado Warning issued June 1 at 2:05PM EDT until June 1 at 2:45PM EDT by NWS Charlotte",
+                            "msgtype": "Alert",
+                            "severity": "Extreme",
+                            "urgency": "Immediate",
+                            "event": "Tornado Warning",
+                            "effective": "2024-06-01T14:05:00-04:00",
+                            "expires": "",
+                            "desc": "..."
+                        }
+                    ]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let alerts = Vec::<Alert>::from(response);
+
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].event, "Tornado Warning");
+        assert_eq!(alerts[0].severity, Severity::Extreme);
+        assert_eq!(
+            alerts[0].effective.as_deref(),
+            Some("2024-06-01T14:05:00-04:00")
+        );
+        assert_eq!(alerts[0].expires, None);
+    }
+
+    #[test]
+    fn it_converts_response_without_alerts() {
+        let response: Response =
+            serde_json::from_str(r#"{"alerts": {"alert": []}}"#).unwrap();
+
+        assert!(Vec::<Alert>::from(response).is_empty());
+    }
+}
@@ -1,5 +1,11 @@
-use eyre::WrapErr;
-use serde::Deserialize;
+use std::{
+    collections::hash_map::RandomState, hash::BuildHasher, thread,
+    time::Duration,
+};
+
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::{config::RequestPolicy, Error, Result};
 
 /// The `Fetchable` trait provides a generalized interface for making basic HTTP
 /// requests.
@@ -27,7 +33,13 @@ use serde::Deserialize;
 /// }
 ///
 /// impl Fetchable<YourResponseType, YourOutputType> for SearchClient {
-///     const URL: &'static str = "https://your.api/endpoint";
+///     fn provider(&self) -> &'static str {
+///         "search"
+///     }
+///
+///     fn url(&self) -> &'static str {
+///         "https://your.api/endpoint"
+///     }
 ///
 ///     fn query(&self) -> Option<&Vec<(String, String)>> {
 ///         Some(&self.query)
@@ -37,13 +49,18 @@ use serde::Deserialize;
 ///
 /// # Errors
 ///
-/// Returns `eyre::Result` which is a custom type alias over standard `Result`.
-/// Typically, errors will be of type `eyre::Report`.
+/// Returns `crate::Result`, HTTP failures are mapped onto the `crate::Error`
+/// variant describing them (eg. `RateLimited` or `InvalidApiKey`).
 pub trait Fetchable<T, U>
 where
     for<'de> T: Deserialize<'de>,
-    U: From<T>,
+    U: TryFrom<T>,
+    Error: From<<U as TryFrom<T>>::Error>,
 {
+    /// Name of the provider, used in errors and to look up its
+    /// `RequestPolicy`.
+    fn provider(&self) -> &'static str;
+
     /// Provides the API endpoint to fetch data from.
     fn url(&self) -> &'static str;
 
@@ -51,20 +68,57 @@ where
     ///
     /// # Returns
     ///
-    /// Returns `eyre::Result<U>` where `U` is the type that the deserialized
-    /// response will be converted into.
-    fn fetch(&self) -> eyre::Result<U> {
+    /// Returns `Result<U>` where `U` is the type that the deserialized
+    /// response will be converted into, conversion failures are surfaced as
+    /// `crate::Error` too.
+    fn fetch(&self) -> Result<U> {
         if !self.is_valid() {
-            return Err(eyre::eyre!("provider is not in a valid state"));
+            return Err(Error::Unavailable {
+                provider: self.provider().to_string(),
+            });
         }
 
-        ureq::get(self.url())
-            .query_pairs(self.query_pairs())
-            .call()
-            .map_err(|_| eyre::eyre!("unknown error"))?
-            .into_json::<T>()
-            .wrap_err(format!("error parsing response from: {}", self.url()))
-            .map(U::from)
+        let response = self.get()?;
+
+        U::try_from(self.parse::<T>(response)?).map_err(Error::from)
+    }
+
+    /// Makes the HTTP GET request, applying the timeouts of the provider's
+    /// `RequestPolicy` and retrying transient failures.
+    ///
+    /// # Returns
+    ///
+    /// Returns the raw `ureq::Response` so that implementations overriding
+    /// `fetch` can deserialize it into a different shape.
+    #[allow(clippy::result_large_err)]
+    fn get(&self) -> Result<ureq::Response> {
+        let policy = self.policy();
+        let agent = agent(&policy);
+
+        with_retries(&policy, || {
+            agent.get(self.url()).query_pairs(self.query_pairs()).call()
+        })
+        .map_err(|err| {
+            log::debug!("request to {} failed: {err}", self.provider());
+
+            Error::from_ureq(self.provider(), &err)
+        })
+    }
+
+    /// Deserializes the JSON body of `response`.
+    fn parse<R>(&self, response: ureq::Response) -> Result<R>
+    where
+        R: DeserializeOwned,
+    {
+        response.into_json::<R>().map_err(|err| Error::Parse {
+            provider: self.provider().to_string(),
+            message: err.to_string(),
+        })
+    }
+
+    /// Returns the timeouts and retry behaviour to use for this provider.
+    fn policy(&self) -> RequestPolicy {
+        RequestPolicy::default()
     }
 
     /// Checks if the provider is valid for fetching data.
@@ -107,3 +161,220 @@ where
         }
     }
 }
+
+/// Builds an HTTP agent using the timeouts from `policy`.
+pub(crate) fn agent(policy: &RequestPolicy) -> ureq::Agent {
+    // Nominatim and the National Weather Service ask for an identifying
+    // user agent
+    ureq::AgentBuilder::new()
+        .user_agent(concat!("conditions/", env!("CARGO_PKG_VERSION")))
+        .timeout_connect(Duration::from_millis(policy.connect_timeout_ms))
+        .timeout_read(Duration::from_millis(policy.read_timeout_ms))
+        .build()
+}
+
+/// Invokes `request` until it succeeds, fails with an error that is not
+/// transient, or `policy.retries` retries have been spent.
+///
+/// # Errors
+///
+/// Returns the error from the last attempt.
+#[allow(clippy::result_large_err)]
+pub(crate) fn with_retries<F>(
+    policy: &RequestPolicy,
+    mut request: F,
+) -> std::result::Result<ureq::Response, ureq::Error>
+where
+    F: FnMut() -> std::result::Result<ureq::Response, ureq::Error>,
+{
+    let mut attempt = 0;
+
+    loop {
+        match request() {
+            Err(err) if attempt < policy.retries && is_transient(&err) => {
+                log::debug!("retrying transient failure: {err}");
+
+                thread::sleep(backoff(policy, attempt));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Server errors and connection level failures (refused, reset or timed out)
+/// are worth retrying, anything else will fail the same way again.
+fn is_transient(err: &ureq::Error) -> bool {
+    match err {
+        ureq::Error::Status(status, _) => *status >= 500,
+        ureq::Error::Transport(transport) => matches!(
+            transport.kind(),
+            ureq::ErrorKind::ConnectionFailed | ureq::ErrorKind::Io
+        ),
+    }
+}
+
+/// Exponential backoff with "equal jitter": half of the delay is fixed, the
+/// other half is random so that clients behind the same NAT do not retry in
+/// lockstep.
+fn backoff(policy: &RequestPolicy, attempt: u32) -> Duration {
+    let delay = policy
+        .backoff_ms
+        .saturating_mul(2_u64.saturating_pow(attempt))
+        .min(policy.max_backoff_ms);
+    let half = delay / 2;
+    let jitter = RandomState::new().hash_one(attempt) % (half + 1);
+
+    Duration::from_millis(half + jitter)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    /// Serves each of `responses` (status, body) to one connection in turn and
+    /// returns the URL to reach the server on.
+    fn serve(responses: Vec<(u16, &'static str)>) -> &'static str {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+
+        thread::spawn(move || {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+            }
+        });
+
+        Box::leak(url.into_boxed_str())
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Response {
+        value: i32,
+    }
+
+    struct Client {
+        url: &'static str,
+        policy: RequestPolicy,
+    }
+
+    impl Fetchable<Response, i32> for Client {
+        fn provider(&self) -> &'static str {
+            "test"
+        }
+
+        fn url(&self) -> &'static str {
+            self.url
+        }
+
+        fn policy(&self) -> RequestPolicy {
+            self.policy
+        }
+    }
+
+    impl From<Response> for i32 {
+        fn from(response: Response) -> Self {
+            response.value
+        }
+    }
+
+    fn policy(retries: u32) -> RequestPolicy {
+        RequestPolicy {
+            retries,
+            backoff_ms: 1,
+            max_backoff_ms: 2,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_retries_server_errors() {
+        let url = serve(vec![(503, ""), (200, r#"{"value":42}"#)]);
+        let client = Client {
+            url,
+            policy: policy(1),
+        };
+
+        assert_eq!(client.fetch().unwrap(), 42);
+    }
+
+    #[test]
+    fn it_gives_up_after_retries_are_spent() {
+        let url = serve(vec![(503, ""), (503, ""), (200, r#"{"value":42}"#)]);
+        let client = Client {
+            url,
+            policy: policy(1),
+        };
+
+        assert!(matches!(
+            client.fetch(),
+            Err(Error::Network {
+                status: Some(503),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn it_does_not_retry_client_errors() {
+        let url = serve(vec![(404, ""), (200, r#"{"value":42}"#)]);
+        let client = Client {
+            url,
+            policy: policy(3),
+        };
+
+        assert!(matches!(
+            client.fetch(),
+            Err(Error::Network {
+                status: Some(404),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn it_maps_status_codes_to_errors() {
+        let url = serve(vec![(401, ""), (429, ""), (200, "nope")]);
+        let client = Client {
+            url,
+            policy: policy(0),
+        };
+
+        assert!(matches!(client.fetch(), Err(Error::InvalidApiKey { .. })));
+        assert!(matches!(client.fetch(), Err(Error::RateLimited { .. })));
+        assert!(matches!(client.fetch(), Err(Error::Parse { .. })));
+    }
+
+    #[test]
+    fn backoff_grows_and_is_capped() {
+        let policy = RequestPolicy {
+            backoff_ms: 100,
+            max_backoff_ms: 1_000,
+            ..Default::default()
+        };
+
+        for attempt in 0..10 {
+            let delay = backoff(&policy, attempt).as_millis();
+            let expected = (100_u128 << attempt).min(1_000);
+
+            assert!(delay >= expected / 2 && delay <= expected);
+        }
+    }
+}
@@ -0,0 +1,544 @@
+use std::{
+    f64::consts::TAU,
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+use crate::{location::Location, Error, Result};
+
+/// Julian day of 2000-01-01T12:00:00Z.
+const J2000: f64 = 2_451_545.0;
+/// Julian day of 1970-01-01T00:00:00Z.
+const UNIX_EPOCH_JULIAN_DAY: f64 = 2_440_587.5;
+const SECONDS_PER_DAY: f64 = 86_400.0;
+/// Obliquity of the ecliptic, in degrees.
+const OBLIQUITY: f64 = 23.4397;
+/// Altitude of the sun's centre at sunrise and sunset, accounting for
+/// refraction and the radius of its disc.
+pub(crate) const SUNRISE_ALTITUDE: f64 = -0.833;
+/// Julian day of the new moon of 2000-01-06T18:14Z.
+const NEW_MOON: f64 = 2_451_550.26;
+/// Mean length of a lunar cycle, in days.
+const SYNODIC_MONTH: f64 = 29.530_588_853;
+
+/// The position of the sun and moon, computed offline from the coordinates
+/// of a location.
+///
+/// Times are UTC timestamps rounded to the minute (eg.
+/// `2024-06-21T10:09:00Z`) for the solar day closest to the requested time,
+/// and are accurate to a minute or two.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Astro {
+    /// `None` when the sun doesn't rise that day (polar day or night).
+    pub sunrise: Option<String>,
+    /// `None` when the sun doesn't set that day (polar day or night).
+    pub sunset: Option<String>,
+    pub solar_noon: String,
+    /// Seconds between sunrise and sunset, 0 during polar night and a full
+    /// day during polar day.
+    pub day_length_secs: u64,
+    /// Sun 6° below the horizon.
+    pub civil_twilight: Twilight,
+    /// Sun 12° below the horizon.
+    pub nautical_twilight: Twilight,
+    /// Sun 18° below the horizon.
+    pub astronomical_twilight: Twilight,
+    /// Degrees above the horizon at the requested time, negative below it.
+    pub solar_elevation: f64,
+    pub moon: Moon,
+}
+
+/// When twilight begins in the morning and ends in the evening, `None` when
+/// the sun doesn't get that far below the horizon (or always is).
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Twilight {
+    pub dawn: Option<String>,
+    pub dusk: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Moon {
+    pub phase: MoonPhase,
+    pub icon: &'static str,
+    /// Percent of the disc that is lit.
+    pub illumination: f64,
+    /// Days since the last new moon.
+    pub age_days: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoonPhase {
+    New,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    Full,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+
+impl MoonPhase {
+    const ALL: [MoonPhase; 8] = [
+        MoonPhase::New,
+        MoonPhase::WaxingCrescent,
+        MoonPhase::FirstQuarter,
+        MoonPhase::WaxingGibbous,
+        MoonPhase::Full,
+        MoonPhase::WaningGibbous,
+        MoonPhase::LastQuarter,
+        MoonPhase::WaningCrescent,
+    ];
+
+    /// The phase an eighth of the cycle wide around `fraction` (0 at new
+    /// moon, 0.5 at full moon).
+    fn of(fraction: f64) -> Self {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let eighth = (fraction * 8.0).round() as usize;
+
+        Self::ALL[eighth % Self::ALL.len()]
+    }
+
+    /// Nerd font glyph for the phase.
+    #[must_use]
+    pub fn icon(self) -> &'static str {
+        match self {
+            MoonPhase::New => "\u{e38d}",
+            MoonPhase::WaxingCrescent => "\u{e390}",
+            MoonPhase::FirstQuarter => "\u{e394}",
+            MoonPhase::WaxingGibbous => "\u{e397}",
+            MoonPhase::Full => "\u{e39b}",
+            MoonPhase::WaningGibbous => "\u{e39e}",
+            MoonPhase::LastQuarter => "\u{e3a2}",
+            MoonPhase::WaningCrescent => "\u{e3a5}",
+        }
+    }
+}
+
+impl fmt::Display for MoonPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            MoonPhase::New => "new moon",
+            MoonPhase::WaxingCrescent => "waxing crescent",
+            MoonPhase::FirstQuarter => "first quarter",
+            MoonPhase::WaxingGibbous => "waxing gibbous",
+            MoonPhase::Full => "full moon",
+            MoonPhase::WaningGibbous => "waning gibbous",
+            MoonPhase::LastQuarter => "last quarter",
+            MoonPhase::WaningCrescent => "waning crescent",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl Astro {
+    /// Computes the position of the sun and moon at `location` now.
+    ///
+    /// # Errors
+    ///
+    /// `Error::InvalidLocation` if the coordinates of `location` aren't
+    /// numbers.
+    pub fn now(location: &Location) -> Result<Self> {
+        Self::at(location, now())
+    }
+
+    /// Computes the position of the sun and moon at `location` at `time`
+    /// (Unix seconds).
+    ///
+    /// # Errors
+    ///
+    /// `Error::InvalidLocation` if the coordinates of `location` aren't
+    /// numbers.
+    pub fn at(location: &Location, time: i64) -> Result<Self> {
+        let (latitude, longitude) = coordinates(location)?;
+        let day = SolarDay::new(latitude, longitude, time);
+        let sun = day.crossing(SUNRISE_ALTITUDE);
+        let twilight = |depression: f64| {
+            let crossing = day.crossing(-depression);
+
+            Twilight {
+                dawn: crossing.rise().map(timestamp),
+                dusk: crossing.set().map(timestamp),
+            }
+        };
+
+        Ok(Self {
+            sunrise: sun.rise().map(timestamp),
+            sunset: sun.set().map(timestamp),
+            solar_noon: timestamp(day.transit),
+            day_length_secs: sun.length(),
+            civil_twilight: twilight(6.0),
+            nautical_twilight: twilight(12.0),
+            astronomical_twilight: twilight(18.0),
+            solar_elevation: round(solar_elevation(latitude, longitude, time)),
+            moon: Moon::at(time),
+        })
+    }
+}
+
+impl Moon {
+    /// Uses the mean length of a lunar cycle, so the phase can be off by
+    /// several hours.
+    fn at(time: i64) -> Self {
+        let fraction =
+            ((julian_day(time) - NEW_MOON) / SYNODIC_MONTH).rem_euclid(1.0);
+        let phase = MoonPhase::of(fraction);
+
+        Self {
+            phase,
+            icon: phase.icon(),
+            illumination: round((1.0 - (TAU * fraction).cos()) * 50.0),
+            age_days: round(fraction * SYNODIC_MONTH),
+        }
+    }
+}
+
+/// The solar day, from one solar midnight to the next at a longitude.
+struct SolarDay {
+    latitude: f64,
+    declination: f64,
+    /// Julian day of solar noon.
+    transit: f64,
+}
+
+/// When the sun crosses an altitude during a `SolarDay`.
+enum Crossing {
+    /// Julian days of the crossings in the morning and in the evening.
+    Twice(f64, f64),
+    Above,
+    Below,
+}
+
+impl SolarDay {
+    /// Uses the sunrise equation, see
+    /// <https://en.wikipedia.org/wiki/Sunrise_equation>.
+    fn new(latitude: f64, longitude: f64, time: i64) -> Self {
+        // the solar day whose noon is closest to `time`
+        let days = (julian_day(time) - J2000 + longitude / 360.0).round()
+            - longitude / 360.0;
+        let sun = Sun::at(days);
+        let transit = J2000 + days + 0.0053 * sun.mean_anomaly.sin()
+            - 0.0069 * (2.0 * sun.ecliptic_longitude).sin();
+
+        Self {
+            latitude: latitude.to_radians(),
+            declination: sun.declination,
+            transit,
+        }
+    }
+
+    fn crossing(&self, altitude: f64) -> Crossing {
+        let cos_hour_angle = (altitude.to_radians().sin()
+            - self.latitude.sin() * self.declination.sin())
+            / (self.latitude.cos() * self.declination.cos());
+
+        if cos_hour_angle > 1.0 {
+            Crossing::Below
+        } else if cos_hour_angle < -1.0 {
+            Crossing::Above
+        } else {
+            let offset = cos_hour_angle.acos() / TAU;
+
+            Crossing::Twice(self.transit - offset, self.transit + offset)
+        }
+    }
+}
+
+impl Crossing {
+    fn rise(&self) -> Option<f64> {
+        match self {
+            Crossing::Twice(rise, _) => Some(*rise),
+            Crossing::Above | Crossing::Below => None,
+        }
+    }
+
+    fn set(&self) -> Option<f64> {
+        match self {
+            Crossing::Twice(_, set) => Some(*set),
+            Crossing::Above | Crossing::Below => None,
+        }
+    }
+
+    /// Seconds spent above the altitude.
+    fn length(&self) -> u64 {
+        match self {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            Crossing::Twice(rise, set) => {
+                ((set - rise) * SECONDS_PER_DAY).round() as u64
+            }
+            Crossing::Above => 86_400,
+            Crossing::Below => 0,
+        }
+    }
+}
+
+/// Position of the sun on the celestial sphere, angles in radians.
+struct Sun {
+    mean_anomaly: f64,
+    ecliptic_longitude: f64,
+    declination: f64,
+    right_ascension: f64,
+}
+
+impl Sun {
+    /// Position `days` after J2000.
+    fn at(days: f64) -> Self {
+        let mean_anomaly = (357.5291 + 0.985_600_28 * days).to_radians();
+        let center = (1.9148 * mean_anomaly.sin()
+            + 0.02 * (2.0 * mean_anomaly).sin()
+            + 0.0003 * (3.0 * mean_anomaly).sin())
+        .to_radians();
+        let ecliptic_longitude =
+            mean_anomaly + center + (102.9372_f64 + 180.0).to_radians();
+        let obliquity = OBLIQUITY.to_radians();
+
+        Self {
+            mean_anomaly,
+            ecliptic_longitude,
+            declination: (ecliptic_longitude.sin() * obliquity.sin()).asin(),
+            right_ascension: (ecliptic_longitude.sin() * obliquity.cos())
+                .atan2(ecliptic_longitude.cos()),
+        }
+    }
+}
+
+/// Degrees the sun is above the horizon at the coordinates at `time` (Unix
+/// seconds), negative below it.
+pub(crate) fn solar_elevation(latitude: f64, longitude: f64, time: i64) -> f64 {
+    let days = julian_day(time) - J2000;
+    let sun = Sun::at(days);
+    let sidereal_time =
+        (280.1470 + 360.985_623_5 * days + longitude).to_radians();
+    let hour_angle = sidereal_time - sun.right_ascension;
+    let latitude = latitude.to_radians();
+
+    (latitude.sin() * sun.declination.sin()
+        + latitude.cos() * sun.declination.cos() * hour_angle.cos())
+    .asin()
+    .to_degrees()
+}
+
+/// Seconds since the Unix epoch.
+pub(crate) fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| {
+            i64::try_from(elapsed.as_secs()).unwrap_or(i64::MAX)
+        })
+}
+
+/// Parses a UTC time such as `2024-06-21T16:00`, as reported by Open-Meteo
+/// when no timezone is requested, into seconds since the Unix epoch.
+pub(crate) fn parse_time(time: &str) -> Option<i64> {
+    let (date, clock) = time.split_once('T')?;
+    let (hour, minute) = clock.split_once(':')?;
+
+    if !crate::history::is_date(date)
+        || ![hour, minute].iter().all(|part| {
+            part.len() == 2 && part.bytes().all(|b| b.is_ascii_digit())
+        })
+    {
+        return None;
+    }
+
+    let mut parts = date.split('-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    let (hour, minute) =
+        (hour.parse::<i64>().ok()?, minute.parse::<i64>().ok()?);
+
+    if hour > 23 || minute > 59 {
+        return None;
+    }
+
+    Some(((days(year, month, day) * 24 + hour) * 60 + minute) * 60)
+}
+
+fn coordinates(location: &Location) -> Result<(f64, f64)> {
+    let parse = |coordinate: &str| {
+        coordinate.trim().parse::<f64>().map_err(|_| {
+            Error::InvalidLocation(format!(
+                "invalid coordinates \"{},{}\"",
+                location.latitude, location.longitude
+            ))
+        })
+    };
+
+    Ok((parse(&location.latitude)?, parse(&location.longitude)?))
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn julian_day(time: i64) -> f64 {
+    time as f64 / SECONDS_PER_DAY + UNIX_EPOCH_JULIAN_DAY
+}
+
+/// Formats the Julian day as a UTC timestamp, rounded to the minute.
+fn timestamp(julian_day: f64) -> String {
+    #[allow(clippy::cast_possible_truncation)]
+    let minutes =
+        ((julian_day - UNIX_EPOCH_JULIAN_DAY) * 1_440.0).round() as i64;
+    let (year, month, day) = civil(minutes.div_euclid(1_440));
+    let minute = minutes.rem_euclid(1_440);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:00Z",
+        minute / 60,
+        minute % 60
+    )
+}
+
+/// Converts days since the Unix epoch to a (year, month, day) date, see
+/// <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524
+        - day_of_era / 146_096)
+        / 365;
+    let day_of_year =
+        day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+/// Converts a date to days since the Unix epoch, the inverse of `civil`, see
+/// <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+fn days(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era =
+        year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Rounds to a tenth, finer precision than the approximations allow.
+fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-06-21T16:00:00Z
+    const SOLSTICE: i64 = 1_718_985_600;
+
+    fn charlotte() -> Location {
+        Location::from_coordinates(35.23, -80.84).unwrap()
+    }
+
+    #[test]
+    fn it_computes_sun_times() {
+        let astro = Astro::at(&charlotte(), SOLSTICE).unwrap();
+
+        assert_eq!(astro.sunrise.as_deref(), Some("2024-06-21T10:09:00Z"));
+        assert_eq!(astro.sunset.as_deref(), Some("2024-06-22T00:41:00Z"));
+        assert_eq!(astro.solar_noon, "2024-06-21T17:25:00Z");
+        assert_eq!(astro.day_length_secs / 60, 14 * 60 + 32);
+        assert_eq!(
+            astro.civil_twilight.dawn.as_deref(),
+            Some("2024-06-21T09:39:00Z")
+        );
+        assert_eq!(
+            astro.nautical_twilight.dusk.as_deref(),
+            Some("2024-06-22T01:48:00Z")
+        );
+        assert_eq!(
+            astro.astronomical_twilight.dawn.as_deref(),
+            Some("2024-06-21T08:21:00Z")
+        );
+        assert!((astro.solar_elevation - 68.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn it_handles_polar_day_and_night() {
+        // 2024-12-21T12:00:00Z
+        let time = 1_734_782_400;
+        let svalbard = Location::from_coordinates(78.22, 15.65).unwrap();
+        let mcmurdo = Location::from_coordinates(-77.85, 166.67).unwrap();
+
+        let night = Astro::at(&svalbard, time).unwrap();
+        let day = Astro::at(&mcmurdo, time).unwrap();
+
+        assert_eq!(night.sunrise, None);
+        assert_eq!(night.day_length_secs, 0);
+        assert!(night.solar_elevation < 0.0);
+        assert_eq!(day.sunset, None);
+        assert_eq!(day.day_length_secs, 86_400);
+        assert!(day.solar_elevation > 0.0);
+    }
+
+    #[test]
+    fn it_computes_moon_phase() {
+        // full moon of 2024-06-22T01:08Z
+        let full = Moon::at(1_719_018_480);
+        // new moon of 2024-07-05T22:57Z
+        let new = Moon::at(1_720_220_220);
+
+        assert_eq!(full.phase, MoonPhase::Full);
+        assert!(full.illumination > 99.0);
+        assert_eq!(new.phase, MoonPhase::New);
+        assert!(new.illumination < 1.0);
+        // first quarter of 2024-06-14T05:18Z
+        assert_eq!(Moon::at(1_718_342_280).phase, MoonPhase::FirstQuarter);
+    }
+
+    #[test]
+    fn it_formats_timestamps() {
+        assert_eq!(timestamp(UNIX_EPOCH_JULIAN_DAY), "1970-01-01T00:00:00Z");
+        assert_eq!(timestamp(J2000), "2000-01-01T12:00:00Z");
+        assert_eq!(timestamp(2_460_369.5), "2024-02-29T00:00:00Z");
+    }
+
+    #[test]
+    fn it_parses_times() {
+        assert_eq!(parse_time("1970-01-01T00:00"), Some(0));
+        assert_eq!(parse_time("2024-06-21T16:00"), Some(SOLSTICE));
+        assert_eq!(parse_time("2024-02-29T23:59"), Some(1_709_251_140));
+
+        for time in [
+            "",
+            "2024-06-21",
+            "2024-06-21T24:00",
+            "2024-06-21T16:60",
+            "2024-06-21T+6:00",
+            "2023-02-29T00:00",
+            "2024-06-21T16:00:00Z",
+        ] {
+            assert_eq!(parse_time(time), None, "{time}");
+        }
+    }
+
+    #[test]
+    fn it_converts_dates_to_days() {
+        for days in [-719_468, -1, 0, 11_016, 19_782, 19_895] {
+            let (year, month, day) = civil(days);
+
+            assert_eq!(super::days(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn it_rejects_invalid_coordinates() {
+        let location = Location {
+            latitude: "north".to_string(),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            Astro::at(&location, SOLSTICE),
+            Err(Error::InvalidLocation(_))
+        ));
+    }
+}
@@ -1,9 +1,207 @@
-use std::env;
+use std::{collections::HashSet, env, fmt};
 
-use eyre::WrapErr;
-use sqlx::{Connection, SqliteConnection};
+use sqlx::{Connection, Executor, FromRow, SqliteConnection};
 
-use crate::location::Location;
+use crate::{
+    config::ParseConfigError,
+    history::{Archive, Observation},
+    location::{geodata::PostalCode, Location},
+    weather::CurrentConditions,
+    Result, Unit,
+};
+
+/// Schema migrations, `MIGRATIONS[n]` upgrades a database from version `n`
+/// (as recorded in `PRAGMA user_version`) to `n + 1`.
+///
+/// Databases created before migrations were introduced are at version 0,
+/// their `cache` table is dropped as entries keyed by postal code alone can
+/// hold the wrong country's location.
+const MIGRATIONS: &[&str] = &[
+    r"
+    DROP TABLE IF EXISTS cache;
+
+    CREATE TABLE location (
+        id          INTEGER PRIMARY KEY,
+        postal_code TEXT NOT NULL,
+        country     TEXT NOT NULL,
+        loc         TEXT NOT NULL,
+        latitude    TEXT NOT NULL,
+        longitude   TEXT NOT NULL,
+        name        TEXT
+    );
+
+    -- locations found by place name may have no postal code
+    CREATE UNIQUE INDEX location_postal_code
+    ON location (postal_code, country)
+    WHERE postal_code != '';
+
+    CREATE TABLE alias (
+        region      TEXT PRIMARY KEY,
+        location_id INTEGER NOT NULL REFERENCES location (id) ON DELETE CASCADE
+    );
+
+    CREATE TABLE IF NOT EXISTS ip_location (
+        network     TEXT NOT NULL,
+        postal_code TEXT NOT NULL,
+        loc         TEXT NOT NULL,
+        latitude    TEXT NOT NULL,
+        longitude   TEXT NOT NULL,
+        name        TEXT,
+        fetched_at  INTEGER NOT NULL,
+
+        UNIQUE(network)
+    );
+
+    CREATE TABLE IF NOT EXISTS geonames (
+        country_code TEXT NOT NULL,
+        postal_code  TEXT NOT NULL COLLATE NOCASE,
+        place_name   TEXT NOT NULL,
+        admin_name1  TEXT NOT NULL,
+        latitude     REAL NOT NULL,
+        longitude    REAL NOT NULL,
+
+        UNIQUE(country_code, postal_code)
+    );
+",
+    r"
+    ALTER TABLE alias ADD COLUMN cached_at INTEGER NOT NULL DEFAULT 0;
+
+    UPDATE alias SET cached_at = CAST(strftime('%s', 'now') AS INTEGER);
+
+    CREATE TABLE stats (
+        kind   TEXT PRIMARY KEY,
+        hits   INTEGER NOT NULL DEFAULT 0,
+        misses INTEGER NOT NULL DEFAULT 0
+    );
+",
+    r"
+    CREATE TABLE observations (
+        id             INTEGER PRIMARY KEY,
+        observed_at    INTEGER NOT NULL,
+        region         TEXT,
+        name           TEXT,
+        latitude       TEXT NOT NULL,
+        longitude      TEXT NOT NULL,
+        postal_code    TEXT NOT NULL,
+        provider       TEXT NOT NULL,
+        temp           REAL NOT NULL,
+        unit           TEXT NOT NULL,
+        condition_code INTEGER NOT NULL,
+        condition      TEXT NOT NULL,
+        is_day         INTEGER NOT NULL,
+        icon           TEXT NOT NULL
+    );
+
+    CREATE INDEX observations_observed_at ON observations (observed_at);
+",
+    r"
+    CREATE TABLE daily (
+        latitude      TEXT NOT NULL,
+        longitude     TEXT NOT NULL,
+        name          TEXT,
+        date          TEXT NOT NULL,
+        unit          TEXT NOT NULL,
+        weather_code  INTEGER,
+        temp_max      REAL,
+        temp_min      REAL,
+        temp_mean     REAL,
+        precipitation REAL,
+
+        UNIQUE(latitude, longitude, date)
+    );
+
+    CREATE TABLE hourly (
+        latitude          TEXT NOT NULL,
+        longitude         TEXT NOT NULL,
+        name              TEXT,
+        time              TEXT NOT NULL,
+        unit              TEXT NOT NULL,
+        weather_code      INTEGER,
+        temp              REAL,
+        relative_humidity REAL,
+        precipitation     REAL,
+
+        UNIQUE(latitude, longitude, time)
+    );
+",
+    r"
+    CREATE TABLE place_name (
+        latitude  TEXT NOT NULL,
+        longitude TEXT NOT NULL,
+        name      TEXT NOT NULL,
+        cached_at INTEGER NOT NULL,
+
+        UNIQUE(latitude, longitude)
+    );
+",
+];
+
+/// Prefix distinguishing IP-derived locations (keyed by network) from regions
+/// in `Entry::key`.
+const IP_KEY_PREFIX: &str = "ip:";
+
+/// A cached location as listed by `Cache::entries`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    /// The region the location was looked up by, or `ip:` followed by the
+    /// network for locations derived from the IP address.
+    pub key: String,
+    pub location: Location,
+    /// Seconds since the entry was cached.
+    pub age_secs: u64,
+}
+
+#[derive(FromRow)]
+struct EntryRow {
+    key: String,
+    #[sqlx(flatten)]
+    location: Location,
+    age_secs: i64,
+}
+
+/// Hit and miss counters for one kind of lookup.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Counter {
+    pub entries: u64,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl fmt::Display for Counter {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let lookups = self.hits + self.misses;
+        #[allow(clippy::cast_precision_loss)]
+        let rate = if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64 * 100.0
+        };
+
+        write!(
+            fmt,
+            "{} entries, {} hits, {} misses ({rate:.0}% hit rate)",
+            self.entries, self.hits, self.misses,
+        )
+    }
+}
+
+/// Cache usage as reported by `Cache::stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub locations: Counter,
+    pub ip_locations: Counter,
+    pub postal_codes: u64,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "Locations: {}\nIP Locations: {}\nImported Postal Codes: {}",
+            self.locations, self.ip_locations, self.postal_codes,
+        )
+    }
+}
 
 #[derive(Debug)]
 pub struct Cache {
@@ -16,172 +214,1351 @@ impl Cache {
     /// # Arguments
     ///
     /// * `path` - An optional `String` representing the path to the `SQLite`
-    /// database file. If `Some`, the provided path will be used. If `None`, the
-    /// `DATABASE_URL` environment variable will be used as the path. If the
-    /// `DATABASE_URL` environment variable is not set, an `eyre::Report` error
-    /// will be returned.
+    ///   database file. If `Some`, the provided path will be used. If `None`, the
+    ///   `DATABASE_URL` environment variable will be used as the path. If the
+    ///   `DATABASE_URL` environment variable is not set, an `Error::Config`
+    ///   will be returned.
     ///
     /// # Returns
     ///
     /// Returns a `Result` containing the newly created `Cache` instance on
-    /// success, or an `eyre::Report` error on failure.
+    /// success, or an `Error` on failure.
     ///
     /// # Errors
     ///
     /// This function can return the following errors:
     ///
-    /// * `eyre::Report` - If the `DATABASE_URL` environment variable is not set
-    /// and no `path` is provided.
-    /// * `sqlx::Error` - If there is an error connecting to the `SQLite`
-    /// database or executing the SQL query to create the `cache` table.
-    pub async fn new(path: Option<String>) -> eyre::Result<Self> {
+    /// * `Error::Config` - If the `DATABASE_URL` environment variable is not
+    ///   set and no `path` is provided.
+    /// * `Error::Cache` - If there is an error connecting to the `SQLite`
+    ///   database or migrating its schema.
+    pub async fn new(path: Option<String>) -> Result<Self> {
         let path = match path {
             Some(path) => path,
-            None => {
-                env::var("DATABASE_URL").wrap_err("DATABASE_URL not set")?
-            }
+            None => env::var("DATABASE_URL").map_err(|_| {
+                ParseConfigError::Missing("DATABASE_URL".to_string())
+            })?,
         };
 
         let db_url = format!("sqlite://{path}?mode=rwc");
         let mut connection = SqliteConnection::connect(&db_url).await?;
 
-        sqlx::query(
-            r"
-            CREATE TABLE IF NOT EXISTS cache (
-                postal_code TEXT NOT NULL,
-                loc         TEXT NOT NULL,
-                latitude    TEXT NOT NULL,
-                longitude   TEXT NOT NULL,
+        migrate(&mut connection).await?;
+
+        Ok(Self { connection })
+    }
 
-                UNIQUE(postal_code)
+    /// Caches the location a region resolved to.
+    ///
+    /// The location is stored once per postal code and country, `region` is
+    /// recorded as an alias for it so that later lookups of the same region
+    /// (regardless of case and spacing) find it.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - The region as given by the user, eg. `10001, US`.
+    /// * `country` - The country the postal code belongs to, part of the key
+    ///   as the same postal code exists in several countries.
+    /// * `location` - The location `region` resolved to.
+    ///
+    /// # Errors
+    ///
+    /// This function can raise an error if there is an issue with the SQL query
+    /// execution or the database connection.
+    pub async fn set(
+        &mut self,
+        region: &str,
+        country: &str,
+        location: &Location,
+    ) -> Result<()> {
+        let query = r"
+            INSERT INTO location (
+               postal_code
+              ,country
+              ,loc
+              ,latitude
+              ,longitude
+              ,name
+            )
+            VALUES (
+               ?
+              ,?
+              ,?
+              ,?
+              ,?
+              ,?
             )
+            ON CONFLICT(postal_code, country) WHERE postal_code != ''
+            DO UPDATE SET
+               loc = excluded.loc
+              ,latitude = excluded.latitude
+              ,longitude = excluded.longitude
+              ,name = excluded.name
+            RETURNING id
+            ;
+        ";
+
+        let mut transaction = self.connection.begin().await?;
+
+        let id: i64 = sqlx::query_scalar(query)
+            .bind(&location.postal_code)
+            .bind(normalize(country))
+            .bind(&location.loc)
+            .bind(&location.latitude)
+            .bind(&location.longitude)
+            .bind(&location.name)
+            .fetch_one(&mut *transaction)
+            .await?;
+
+        sqlx::query(
+            r"
+            INSERT OR REPLACE INTO alias (region, location_id, cached_at)
+            VALUES (?, ?, CAST(strftime('%s', 'now') AS INTEGER));
             ",
         )
-        .execute(&mut connection)
+        .bind(normalize(region))
+        .bind(id)
+        .execute(&mut *transaction)
         .await?;
 
-        Ok(Self { connection })
+        transaction.commit().await?;
+
+        Ok(())
     }
 
-    /// Caches the provided location.
-    ///
-    /// This function takes a mutable reference to `self` (the cache) and a
-    /// reference to a `Location` struct.
-    /// It inserts or updates the location in the cache table based on the
-    /// postal code.
+    /// Retrieves the location cached for a region.
     ///
     /// # Arguments
     ///
-    /// * `location` - A reference to a `Location` struct containing the details
-    /// of the location to be inserted or updated.
+    /// * `region` - The region as given by the user, compared ignoring case
+    ///   and spacing around commas.
     ///
     /// # Returns
     ///
-    /// This function returns a `Result` indicating success or failure. If the
-    /// operation is successful, it returns `Ok(())`.
-    /// If an error occurs during the execution of the SQL query or the database
-    /// connection, it returns an `eyre::Result` with the error details.
+    /// * `Result<Option<Location>>` - A result that contains an optional
+    ///   `Location` if found in the cache, or an `Error::Cache` if an error
+    ///   occurred.
     ///
     /// # Errors
     ///
-    /// This function can raise an error if there is an issue with the SQL query
-    /// execution or the database connection.
-    /// The specific error type is `eyre::Result`, which provides a flexible way
-    /// to handle and propagate errors.
-    pub async fn set(&mut self, location: &Location) -> eyre::Result<()> {
+    /// This function can return an `Error::Cache` if there was an error
+    /// executing the database query or fetching the location from the cache.
+    pub async fn get(&mut self, region: &str) -> Result<Option<Location>> {
         let query = r"
-            INSERT INTO cache (
-               postal_code
+            SELECT
+               location.postal_code
+              ,location.loc
+              ,location.latitude
+              ,location.longitude
+              ,location.name
+            FROM alias
+            JOIN location ON location.id = alias.location_id
+            WHERE alias.region = ?
+            ;
+        ";
+
+        let location: Option<Location> = sqlx::query_as(query)
+            .bind(normalize(region))
+            .fetch_optional(&mut self.connection)
+            .await?;
+
+        self.count("location", location.is_some()).await?;
+
+        Ok(location)
+    }
+
+    /// Caches the location derived from the IP address seen on `network`.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - Identifies the network the machine is on, only one
+    ///   location is kept per network.
+    /// * `location` - The location returned by the IP geolocation provider.
+    ///
+    /// # Errors
+    ///
+    /// This function can return an `Error::Cache` if there was an error
+    /// executing the database query.
+    pub async fn set_ip_location(
+        &mut self,
+        network: &str,
+        location: &Location,
+    ) -> Result<()> {
+        let query = r"
+            INSERT INTO ip_location (
+               network
+              ,postal_code
               ,loc
               ,latitude
               ,longitude
+              ,name
+              ,fetched_at
             )
             VALUES (
                ?
               ,?
               ,?
               ,?
+              ,?
+              ,?
+              ,CAST(strftime('%s', 'now') AS INTEGER)
             )
-            ON CONFLICT(postal_code)
+            ON CONFLICT(network)
             DO UPDATE SET
-               loc = excluded.loc
+               postal_code = excluded.postal_code
+              ,loc = excluded.loc
               ,latitude = excluded.latitude
               ,longitude = excluded.longitude
+              ,name = excluded.name
+              ,fetched_at = excluded.fetched_at
             ;
         ";
 
         sqlx::query(query)
+            .bind(network)
             .bind(&location.postal_code)
             .bind(&location.loc)
             .bind(&location.latitude)
             .bind(&location.longitude)
+            .bind(&location.name)
             .execute(&mut self.connection)
             .await?;
 
         Ok(())
     }
 
-    /// Retrieves a location from the cache based on the provided postal code.
+    /// Retrieves the IP-derived location cached for `network`, as long as it
+    /// was fetched less than `ttl_secs` seconds ago.
     ///
-    /// # Arguments
+    /// # Returns
+    ///
+    /// * `Result<Option<Location>>` - The cached location, or `None` when
+    ///   there is none for `network` or it has expired.
+    ///
+    /// # Errors
+    ///
+    /// This function can return an `Error::Cache` if there was an error
+    /// executing the database query.
+    pub async fn get_ip_location(
+        &mut self,
+        network: &str,
+        ttl_secs: u64,
+    ) -> Result<Option<Location>> {
+        let query = r"
+            SELECT postal_code, loc, latitude, longitude, name
+            FROM ip_location
+            WHERE network = ?
+              AND fetched_at > CAST(strftime('%s', 'now') AS INTEGER) - ?
+            ;
+        ";
+
+        let location: Option<Location> = sqlx::query_as(query)
+            .bind(network)
+            .bind(i64::try_from(ttl_secs).unwrap_or(i64::MAX))
+            .fetch_optional(&mut self.connection)
+            .await?;
+
+        self.count("ip_location", location.is_some()).await?;
+
+        Ok(location)
+    }
+
+    /// Caches the place name reverse geocoded for coordinates, keyed by the
+    /// coordinates rounded to 3 decimals (about 100 m).
     ///
-    /// * `postal_code` - A string representing the postal code of the location
-    /// to retrieve.
+    /// # Errors
+    ///
+    /// This function can return an `Error::Cache` if there was an error
+    /// executing the database query.
+    pub async fn set_place_name(
+        &mut self,
+        latitude: f64,
+        longitude: f64,
+        name: &str,
+    ) -> Result<()> {
+        let query = r"
+            INSERT OR REPLACE INTO place_name (
+               latitude
+              ,longitude
+              ,name
+              ,cached_at
+            )
+            VALUES (?, ?, ?, CAST(strftime('%s', 'now') AS INTEGER))
+            ;
+        ";
+
+        sqlx::query(query)
+            .bind(format!("{latitude:.3}"))
+            .bind(format!("{longitude:.3}"))
+            .bind(name)
+            .execute(&mut self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Retrieves the place name cached for coordinates, see
+    /// `Cache::set_place_name`.
+    ///
+    /// # Errors
+    ///
+    /// This function can return an `Error::Cache` if there was an error
+    /// executing the database query.
+    pub async fn get_place_name(
+        &mut self,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<Option<String>> {
+        let query = r"
+            SELECT name
+            FROM place_name
+            WHERE latitude = ? AND longitude = ?
+            ;
+        ";
+
+        let name = sqlx::query_scalar(query)
+            .bind(format!("{latitude:.3}"))
+            .bind(format!("{longitude:.3}"))
+            .fetch_optional(&mut self.connection)
+            .await?;
+
+        Ok(name)
+    }
+
+    /// Stores postal codes imported from a `GeoNames` dump in a single
+    /// transaction, the postal codes already stored for each country in
+    /// `postal_codes` are removed first.
     ///
     /// # Returns
     ///
-    /// * `Result<Option<Location>, eyre::Report>` - A result that contains an
-    /// optional `Location` if found in the cache, or an `eyre::Report` if an
-    /// error occurred.
+    /// The number of postal codes stored, a postal code listed several times
+    /// for a country counts once.
     ///
     /// # Errors
     ///
-    /// This function can return an `eyre::Report` if there was an error
-    /// executing the database query or fetching the location from the cache.
-    pub async fn get(
+    /// This function can return the first error in `postal_codes`, or an
+    /// `Error::Cache` if there was an error executing the database queries.
+    /// Nothing is changed in either case.
+    pub async fn replace_postal_codes(
+        &mut self,
+        postal_codes: impl IntoIterator<Item = Result<PostalCode>>,
+    ) -> Result<u64> {
+        let query = r"
+            INSERT OR REPLACE INTO geonames (
+               country_code
+              ,postal_code
+              ,place_name
+              ,admin_name1
+              ,latitude
+              ,longitude
+            )
+            VALUES (
+               ?
+              ,?
+              ,?
+              ,?
+              ,?
+              ,?
+            )
+            ;
+        ";
+
+        let mut transaction = self.connection.begin().await?;
+        let mut countries = HashSet::new();
+
+        for postal_code in postal_codes {
+            let postal_code = postal_code?;
+
+            if countries.insert(postal_code.country_code.clone()) {
+                sqlx::query("DELETE FROM geonames WHERE country_code = ?;")
+                    .bind(&postal_code.country_code)
+                    .execute(&mut *transaction)
+                    .await?;
+            }
+
+            sqlx::query(query)
+                .bind(&postal_code.country_code)
+                .bind(&postal_code.postal_code)
+                .bind(&postal_code.place_name)
+                .bind(&postal_code.admin_name1)
+                .bind(postal_code.latitude)
+                .bind(postal_code.longitude)
+                .execute(&mut *transaction)
+                .await?;
+        }
+
+        let mut stored = 0;
+
+        for country in &countries {
+            let count: i64 = sqlx::query_scalar(
+                "SELECT COUNT(*) FROM geonames WHERE country_code = ?;",
+            )
+            .bind(country)
+            .fetch_one(&mut *transaction)
+            .await?;
+
+            stored += u64::try_from(count).unwrap_or_default();
+        }
+
+        transaction.commit().await?;
+
+        Ok(stored)
+    }
+
+    /// Retrieves an imported `GeoNames` postal code.
+    ///
+    /// # Arguments
+    ///
+    /// * `postal_code` - The postal code, compared case insensitively.
+    /// * `country_code` - The upper case two letter country code.
+    ///
+    /// # Errors
+    ///
+    /// This function can return an `Error::Cache` if there was an error
+    /// executing the database query.
+    pub async fn find_postal_code(
         &mut self,
         postal_code: &str,
-    ) -> eyre::Result<Option<Location>> {
-        let query = "SELECT * FROM cache WHERE postal_code = ?;";
+        country_code: &str,
+    ) -> Result<Option<PostalCode>> {
+        let query = r"
+            SELECT * FROM geonames
+            WHERE country_code = ?
+              AND postal_code = ?
+            ;
+        ";
 
-        let location: Option<Location> = sqlx::query_as(query)
+        let postal_code: Option<PostalCode> = sqlx::query_as(query)
+            .bind(country_code)
             .bind(postal_code)
             .fetch_optional(&mut self.connection)
             .await?;
 
-        Ok(location)
+        Ok(postal_code)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::tempdir;
+    /// Lists the cached locations, most recently cached first.
+    ///
+    /// # Errors
+    ///
+    /// This function can return an `Error::Cache` if there was an error
+    /// executing the database query.
+    pub async fn entries(&mut self) -> Result<Vec<Entry>> {
+        let query = r"
+            SELECT
+               alias.region AS key
+              ,location.postal_code
+              ,location.loc
+              ,location.latitude
+              ,location.longitude
+              ,location.name
+              ,CAST(strftime('%s', 'now') AS INTEGER) - alias.cached_at
+                 AS age_secs
+            FROM alias
+            JOIN location ON location.id = alias.location_id
+            UNION ALL
+            SELECT
+               ? || network
+              ,postal_code
+              ,loc
+              ,latitude
+              ,longitude
+              ,name
+              ,CAST(strftime('%s', 'now') AS INTEGER) - fetched_at
+            FROM ip_location
+            ORDER BY 7, 1
+            ;
+        ";
 
-    #[tokio::test]
-    async fn test_cache() {
-        let dir = tempdir().unwrap();
-        let db_path = dir.path().join("test.db");
+        let rows: Vec<EntryRow> = sqlx::query_as(query)
+            .bind(IP_KEY_PREFIX)
+            .fetch_all(&mut self.connection)
+            .await?;
 
-        let mut cache = Cache::new(Some(db_path.display().to_string()))
-            .await
-            .unwrap();
+        Ok(rows
+            .into_iter()
+            .map(|row| Entry {
+                key: row.key,
+                location: row.location,
+                age_secs: u64::try_from(row.age_secs).unwrap_or_default(),
+            })
+            .collect())
+    }
 
-        let location = Location {
-            postal_code: "12345".to_string(),
-            loc: "Test Location".to_string(),
-            latitude: "12.345".to_string(),
-            longitude: "54.321".to_string(),
+    /// Removes the entry listed under `key` by `entries`, the key of a region
+    /// is compared ignoring case and spacing.
+    ///
+    /// # Returns
+    ///
+    /// The number of entries removed.
+    ///
+    /// # Errors
+    ///
+    /// This function can return an `Error::Cache` if there was an error
+    /// executing the database queries.
+    pub async fn remove(&mut self, key: &str) -> Result<u64> {
+        let removed = if let Some(network) = key.strip_prefix(IP_KEY_PREFIX) {
+            sqlx::query("DELETE FROM ip_location WHERE network = ?;")
+                .bind(network)
+                .execute(&mut self.connection)
+                .await?
+                .rows_affected()
+        } else {
+            sqlx::query("DELETE FROM alias WHERE region = ?;")
+                .bind(normalize(key))
+                .execute(&mut self.connection)
+                .await?
+                .rows_affected()
         };
 
-        cache.set(&location).await.unwrap();
+        self.remove_orphans().await?;
 
-        let retrieved_location =
-            cache.get(&location.postal_code).await.unwrap();
+        Ok(removed)
+    }
 
-        assert_eq!(location, retrieved_location.unwrap());
+    /// Removes the entries cached more than `max_age_secs` seconds ago.
+    ///
+    /// # Returns
+    ///
+    /// The number of entries removed.
+    ///
+    /// # Errors
+    ///
+    /// This function can return an `Error::Cache` if there was an error
+    /// executing the database queries.
+    pub async fn prune(&mut self, max_age_secs: u64) -> Result<u64> {
+        let max_age = i64::try_from(max_age_secs).unwrap_or(i64::MAX);
+        let mut removed = 0;
+
+        for query in [
+            "DELETE FROM alias WHERE cached_at < CAST(strftime('%s', 'now') AS INTEGER) - ?;",
+            "DELETE FROM ip_location WHERE fetched_at < CAST(strftime('%s', 'now') AS INTEGER) - ?;",
+            "DELETE FROM place_name WHERE cached_at < CAST(strftime('%s', 'now') AS INTEGER) - ?;",
+        ] {
+            removed += sqlx::query(query)
+                .bind(max_age)
+                .execute(&mut self.connection)
+                .await?
+                .rows_affected();
+        }
+
+        self.remove_orphans().await?;
+
+        Ok(removed)
+    }
+
+    /// Removes every cached location, imported postal codes and the hit/miss
+    /// counters are kept.
+    ///
+    /// # Returns
+    ///
+    /// The number of entries removed.
+    ///
+    /// # Errors
+    ///
+    /// This function can return an `Error::Cache` if there was an error
+    /// executing the database queries.
+    pub async fn clear(&mut self) -> Result<u64> {
+        let mut removed = 0;
+
+        for query in [
+            "DELETE FROM alias;",
+            "DELETE FROM ip_location;",
+            "DELETE FROM place_name;",
+        ] {
+            removed += sqlx::query(query)
+                .execute(&mut self.connection)
+                .await?
+                .rows_affected();
+        }
+
+        self.remove_orphans().await?;
+
+        Ok(removed)
+    }
+
+    /// Reports the number of entries and the hit/miss counters of lookups.
+    ///
+    /// # Errors
+    ///
+    /// This function can return an `Error::Cache` if there was an error
+    /// executing the database queries.
+    pub async fn stats(&mut self) -> Result<Stats> {
+        let query = r"
+            SELECT
+               (SELECT COUNT(*) FROM alias)
+              ,(SELECT COUNT(*) FROM ip_location)
+              ,(SELECT COUNT(*) FROM geonames)
+            ;
+        ";
+
+        let (locations, ip_locations, postal_codes): (i64, i64, i64) =
+            sqlx::query_as(query)
+                .fetch_one(&mut self.connection)
+                .await?;
+        let counters: Vec<(String, i64, i64)> =
+            sqlx::query_as("SELECT kind, hits, misses FROM stats;")
+                .fetch_all(&mut self.connection)
+                .await?;
+        let counter = |kind: &str, entries: i64| {
+            let (hits, misses) = counters
+                .iter()
+                .find(|(name, _, _)| name == kind)
+                .map_or((0, 0), |(_, hits, misses)| (*hits, *misses));
+
+            Counter {
+                entries: u64::try_from(entries).unwrap_or_default(),
+                hits: u64::try_from(hits).unwrap_or_default(),
+                misses: u64::try_from(misses).unwrap_or_default(),
+            }
+        };
+
+        Ok(Stats {
+            locations: counter("location", locations),
+            ip_locations: counter("ip_location", ip_locations),
+            postal_codes: u64::try_from(postal_codes).unwrap_or_default(),
+        })
+    }
+
+    /// Records the conditions reported for `location` in the observation
+    /// history.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - The region as requested, `None` for the configured or
+    ///   detected location.
+    /// * `unit` - The unit the reported temperature is in.
+    ///
+    /// # Errors
+    ///
+    /// This function can return an `Error::Cache` if there was an error
+    /// executing the database query.
+    pub(crate) async fn record(
+        &mut self,
+        region: Option<&str>,
+        location: &Location,
+        conditions: &CurrentConditions,
+        unit: Unit,
+    ) -> Result<()> {
+        let query = r"
+            INSERT INTO observations (
+               observed_at
+              ,region
+              ,name
+              ,latitude
+              ,longitude
+              ,postal_code
+              ,provider
+              ,temp
+              ,unit
+              ,condition_code
+              ,condition
+              ,is_day
+              ,icon
+            )
+            VALUES (
+               CAST(strftime('%s', 'now') AS INTEGER)
+              ,?
+              ,?
+              ,?
+              ,?
+              ,?
+              ,?
+              ,?
+              ,?
+              ,?
+              ,?
+              ,?
+              ,?
+            )
+            ;
+        ";
+        let temp = match unit {
+            Unit::C => conditions.temp_c,
+            Unit::F => conditions.temp_f,
+        };
+
+        sqlx::query(query)
+            .bind(region)
+            .bind(&location.name)
+            .bind(&location.latitude)
+            .bind(&location.longitude)
+            .bind(&location.postal_code)
+            .bind(conditions.source.to_string())
+            .bind(f64::from(temp))
+            .bind(unit.to_string())
+            .bind(conditions.code)
+            .bind(&conditions.condition)
+            .bind(conditions.is_day)
+            .bind(&conditions.icon)
+            .execute(&mut self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Removes the observations recorded more than `max_age_secs` seconds
+    /// ago.
+    ///
+    /// # Returns
+    ///
+    /// The number of observations removed.
+    ///
+    /// # Errors
+    ///
+    /// This function can return an `Error::Cache` if there was an error
+    /// executing the database query.
+    pub(crate) async fn prune_observations(
+        &mut self,
+        max_age_secs: u64,
+    ) -> Result<u64> {
+        let removed = sqlx::query(
+            "DELETE FROM observations WHERE observed_at < CAST(strftime('%s', 'now') AS INTEGER) - ?;",
+        )
+        .bind(i64::try_from(max_age_secs).unwrap_or(i64::MAX))
+        .execute(&mut self.connection)
+        .await?
+        .rows_affected();
+
+        Ok(removed)
+    }
+
+    /// Retrieves the recorded observations, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `since_secs` - Only return observations recorded within this many
+    ///   seconds, `None` returns all of them.
+    ///
+    /// # Errors
+    ///
+    /// This function can return an `Error::Cache` if there was an error
+    /// executing the database query.
+    pub async fn observations(
+        &mut self,
+        since_secs: Option<u64>,
+    ) -> Result<Vec<Observation>> {
+        let query = r"
+            SELECT
+               strftime('%Y-%m-%dT%H:%M:%SZ', observed_at, 'unixepoch')
+                 AS observed_at
+              ,region
+              ,name
+              ,latitude
+              ,longitude
+              ,postal_code
+              ,provider
+              ,temp
+              ,unit
+              ,condition_code
+              ,condition
+              ,is_day
+              ,icon
+            FROM observations
+            WHERE ? IS NULL
+               OR observed_at >= CAST(strftime('%s', 'now') AS INTEGER) - ?
+            ORDER BY observations.observed_at, id
+            ;
+        ";
+        let since =
+            since_secs.map(|secs| i64::try_from(secs).unwrap_or(i64::MAX));
+
+        let observations: Vec<Observation> = sqlx::query_as(query)
+            .bind(since)
+            .bind(since)
+            .fetch_all(&mut self.connection)
+            .await?;
+
+        Ok(observations)
+    }
+
+    /// Stores daily and hourly records fetched from the archive for
+    /// `location` in a single transaction, replacing records already stored
+    /// for the same location and time.
+    ///
+    /// # Arguments
+    ///
+    /// * `unit` - The unit the temperatures are in.
+    ///
+    /// # Errors
+    ///
+    /// This function can return an `Error::Cache` if there was an error
+    /// executing the database queries, in which case nothing is stored.
+    pub async fn store_archive(
+        &mut self,
+        location: &Location,
+        unit: Unit,
+        archive: &Archive,
+    ) -> Result<()> {
+        let daily = r"
+            INSERT OR REPLACE INTO daily (
+               latitude
+              ,longitude
+              ,name
+              ,date
+              ,unit
+              ,weather_code
+              ,temp_max
+              ,temp_min
+              ,temp_mean
+              ,precipitation
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ;
+        ";
+        let hourly = r"
+            INSERT OR REPLACE INTO hourly (
+               latitude
+              ,longitude
+              ,name
+              ,time
+              ,unit
+              ,weather_code
+              ,temp
+              ,relative_humidity
+              ,precipitation
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ;
+        ";
+        let unit = unit.to_string();
+
+        let mut transaction = self.connection.begin().await?;
+
+        for day in &archive.days {
+            sqlx::query(daily)
+                .bind(&location.latitude)
+                .bind(&location.longitude)
+                .bind(&location.name)
+                .bind(&day.date)
+                .bind(&unit)
+                .bind(day.weather_code)
+                .bind(day.temp_max)
+                .bind(day.temp_min)
+                .bind(day.temp_mean)
+                .bind(day.precipitation)
+                .execute(&mut *transaction)
+                .await?;
+        }
+
+        for hour in &archive.hours {
+            sqlx::query(hourly)
+                .bind(&location.latitude)
+                .bind(&location.longitude)
+                .bind(&location.name)
+                .bind(&hour.time)
+                .bind(&unit)
+                .bind(hour.weather_code)
+                .bind(hour.temp)
+                .bind(hour.relative_humidity)
+                .bind(hour.precipitation)
+                .execute(&mut *transaction)
+                .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Records a hit or miss of a `kind` of lookup.
+    async fn count(&mut self, kind: &str, hit: bool) -> Result<()> {
+        let query = r"
+            INSERT INTO stats (kind, hits, misses)
+            VALUES (?, ?, ?)
+            ON CONFLICT(kind)
+            DO UPDATE SET
+               hits = hits + excluded.hits
+              ,misses = misses + excluded.misses
+            ;
+        ";
+
+        sqlx::query(query)
+            .bind(kind)
+            .bind(i64::from(hit))
+            .bind(i64::from(!hit))
+            .execute(&mut self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Removes locations no alias refers to anymore.
+    async fn remove_orphans(&mut self) -> Result<()> {
+        sqlx::query(
+            "DELETE FROM location WHERE id NOT IN (SELECT location_id FROM alias);",
+        )
+        .execute(&mut self.connection)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Applies the `MIGRATIONS` the database hasn't seen yet, each in its own
+/// transaction.
+async fn migrate(connection: &mut SqliteConnection) -> Result<()> {
+    let version: i64 = sqlx::query_scalar("PRAGMA user_version;")
+        .fetch_one(&mut *connection)
+        .await?;
+
+    for (index, migration) in MIGRATIONS
+        .iter()
+        .enumerate()
+        .skip(usize::try_from(version).unwrap_or_default())
+    {
+        let mut transaction = connection.begin().await?;
+
+        transaction.execute(*migration).await?;
+        // pragmas can't be bound
+        transaction
+            .execute(format!("PRAGMA user_version = {};", index + 1).as_str())
+            .await?;
+
+        transaction.commit().await?;
+    }
+
+    Ok(())
+}
+
+/// Normalizes a region for use as a key, ignoring case and spacing around
+/// commas (`10001, US` and `10001,us` are the same region).
+fn normalize(region: &str) -> String {
+    region
+        .split(',')
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(",")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_cache() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        let mut cache = Cache::new(Some(db_path.display().to_string()))
+            .await
+            .unwrap();
+
+        let location = Location {
+            postal_code: "12345".to_string(),
+            loc: "Test Location".to_string(),
+            latitude: "12.345".to_string(),
+            longitude: "54.321".to_string(),
+            name: Some("Testville".to_string()),
+        };
+
+        cache.set("12345,US", "US", &location).await.unwrap();
+
+        let retrieved_location = cache.get("12345, us").await.unwrap();
+
+        assert_eq!(location, retrieved_location.unwrap());
+
+        assert!(cache.get("12345").await.unwrap().is_none());
+        assert!(cache.get("00000,US").await.unwrap().is_none());
+
+        drop(dir);
+    }
+
+    #[tokio::test]
+    async fn it_keys_postal_codes_by_country() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        let mut cache = Cache::new(Some(db_path.display().to_string()))
+            .await
+            .unwrap();
+
+        let new_york = Location {
+            postal_code: "10001".to_string(),
+            loc: "40.7484,-73.9967".to_string(),
+            latitude: "40.7484".to_string(),
+            longitude: "-73.9967".to_string(),
+            name: Some("New York, New York, US".to_string()),
+        };
+        let troyes = Location {
+            postal_code: "10001".to_string(),
+            loc: "48.2973,4.0744".to_string(),
+            latitude: "48.2973".to_string(),
+            longitude: "4.0744".to_string(),
+            name: Some("Troyes, Grand Est, FR".to_string()),
+        };
+
+        cache.set("10001,US", "US", &new_york).await.unwrap();
+        cache.set("10001,FR", "FR", &troyes).await.unwrap();
+        // another alias for an already cached postal code
+        cache.set("10001,usa", "US", &new_york).await.unwrap();
+
+        assert_eq!(
+            cache.get("10001,US").await.unwrap(),
+            Some(new_york.clone())
+        );
+        assert_eq!(cache.get("10001,FR").await.unwrap(), Some(troyes));
+        assert_eq!(cache.get("10001,USA").await.unwrap(), Some(new_york));
+
+        let locations: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM location;")
+                .fetch_one(&mut cache.connection)
+                .await
+                .unwrap();
+
+        assert_eq!(locations, 2);
+
+        drop(dir);
+    }
+
+    #[tokio::test]
+    async fn it_caches_locations_without_postal_code() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        let mut cache = Cache::new(Some(db_path.display().to_string()))
+            .await
+            .unwrap();
+
+        let lisbon = Location {
+            loc: "38.7167,-9.1333".to_string(),
+            name: Some("Lisbon, Lisbon, Portugal".to_string()),
+            ..Default::default()
+        };
+        let porto = Location {
+            loc: "41.1496,-8.611".to_string(),
+            name: Some("Porto, Porto, Portugal".to_string()),
+            ..Default::default()
+        };
+
+        cache.set("Lisbon, PT", "PT", &lisbon).await.unwrap();
+        cache.set("Porto, PT", "PT", &porto).await.unwrap();
+
+        assert_eq!(cache.get("lisbon,pt").await.unwrap(), Some(lisbon));
+        assert_eq!(cache.get("porto,pt").await.unwrap(), Some(porto));
+
+        drop(dir);
+    }
+
+    #[tokio::test]
+    async fn it_migrates_unversioned_database() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db").display().to_string();
+
+        let mut connection =
+            SqliteConnection::connect(&format!("sqlite://{db_path}?mode=rwc"))
+                .await
+                .unwrap();
+
+        connection
+            .execute(
+                r"
+                CREATE TABLE cache (
+                    postal_code TEXT NOT NULL,
+                    loc         TEXT NOT NULL,
+                    latitude    TEXT NOT NULL,
+                    longitude   TEXT NOT NULL,
+
+                    UNIQUE(postal_code)
+                );
+                INSERT INTO cache VALUES ('10001', '48.2,4.0', '48.2', '4.0');
+                ",
+            )
+            .await
+            .unwrap();
+        drop(connection);
+
+        let mut cache = Cache::new(Some(db_path.clone())).await.unwrap();
+
+        let version: i64 = sqlx::query_scalar("PRAGMA user_version;")
+            .fetch_one(&mut cache.connection)
+            .await
+            .unwrap();
+
+        assert_eq!(version, i64::try_from(MIGRATIONS.len()).unwrap());
+        assert!(cache.get("10001").await.unwrap().is_none());
+        drop(cache);
+
+        // reopening doesn't migrate again
+        Cache::new(Some(db_path)).await.unwrap();
+
+        drop(dir);
+    }
+
+    #[tokio::test]
+    async fn it_expires_ip_location() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        let mut cache = Cache::new(Some(db_path.display().to_string()))
+            .await
+            .unwrap();
+
+        let location = Location {
+            postal_code: "28273".to_string(),
+            loc: "35.1287,-80.9338".to_string(),
+            latitude: "35.1287".to_string(),
+            longitude: "-80.9338".to_string(),
+            name: Some("Charlotte, North Carolina, US".to_string()),
+        };
+
+        cache.set_ip_location("10.0.0.2", &location).await.unwrap();
+
+        assert_eq!(
+            cache.get_ip_location("10.0.0.2", 3600).await.unwrap(),
+            Some(location)
+        );
+        assert!(cache
+            .get_ip_location("10.0.0.2", 0)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(cache
+            .get_ip_location("192.168.1.2", 3600)
+            .await
+            .unwrap()
+            .is_none());
+
+        drop(dir);
+    }
+
+    async fn populated() -> (tempfile::TempDir, Cache) {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        let mut cache = Cache::new(Some(db_path.display().to_string()))
+            .await
+            .unwrap();
+
+        let location = Location {
+            postal_code: "28273".to_string(),
+            loc: "35.1287,-80.9338".to_string(),
+            latitude: "35.1287".to_string(),
+            longitude: "-80.9338".to_string(),
+            name: Some("Charlotte, North Carolina, US".to_string()),
+        };
+
+        cache.set("28273, US", "US", &location).await.unwrap();
+        cache.set("Charlotte", "", &location).await.unwrap();
+        cache.set_ip_location("10.0.0.2", &location).await.unwrap();
+
+        (dir, cache)
+    }
+
+    #[tokio::test]
+    async fn it_caches_place_names_by_rounded_coordinates() {
+        let (dir, mut cache) = populated().await;
+
+        cache
+            .set_place_name(35.128_71, -80.933_82, "Charlotte")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            cache.get_place_name(35.1287, -80.9338).await.unwrap(),
+            Some("Charlotte".to_string())
+        );
+        assert_eq!(cache.get_place_name(35.14, -80.9338).await.unwrap(), None);
+
+        cache.clear().await.unwrap();
+
+        assert_eq!(
+            cache.get_place_name(35.1287, -80.9338).await.unwrap(),
+            None
+        );
+
+        drop(dir);
+    }
+
+    #[tokio::test]
+    async fn it_lists_and_removes_entries() {
+        let (dir, mut cache) = populated().await;
+
+        let mut keys: Vec<String> = cache
+            .entries()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.key)
+            .collect();
+
+        keys.sort();
+
+        assert_eq!(keys, ["28273,us", "charlotte", "ip:10.0.0.2"]);
+
+        assert_eq!(cache.remove("28273 , US").await.unwrap(), 1);
+        assert_eq!(cache.remove("ip:10.0.0.2").await.unwrap(), 1);
+        assert_eq!(cache.remove("unknown").await.unwrap(), 0);
+        assert_eq!(cache.entries().await.unwrap().len(), 1);
+
+        assert_eq!(cache.clear().await.unwrap(), 1);
+        assert!(cache.entries().await.unwrap().is_empty());
+        assert_eq!(cache.stats().await.unwrap().locations.entries, 0);
+
+        drop(dir);
+    }
+
+    #[tokio::test]
+    async fn it_prunes_old_entries() {
+        let (dir, mut cache) = populated().await;
+
+        sqlx::query("UPDATE alias SET cached_at = cached_at - 3600 WHERE region = 'charlotte';")
+            .execute(&mut cache.connection)
+            .await
+            .unwrap();
+
+        assert_eq!(cache.prune(60).await.unwrap(), 1);
+        assert!(cache.get("Charlotte").await.unwrap().is_none());
+        assert!(cache.get("28273,US").await.unwrap().is_some());
+        assert_eq!(cache.entries().await.unwrap().len(), 2);
+
+        drop(dir);
+    }
+
+    #[tokio::test]
+    async fn it_counts_hits_and_misses() {
+        let (dir, mut cache) = populated().await;
+
+        cache.get("28273,US").await.unwrap();
+        cache.get("28273,US").await.unwrap();
+        cache.get("10001,US").await.unwrap();
+        cache.get_ip_location("10.0.0.2", 3600).await.unwrap();
+
+        let stats = cache.stats().await.unwrap();
+
+        assert_eq!(
+            stats.locations,
+            Counter {
+                entries: 2,
+                hits: 2,
+                misses: 1
+            }
+        );
+        assert_eq!(
+            stats.ip_locations,
+            Counter {
+                entries: 1,
+                hits: 1,
+                misses: 0
+            }
+        );
+        assert_eq!(
+            stats.locations.to_string(),
+            "2 entries, 2 hits, 1 misses (67% hit rate)"
+        );
+
+        drop(dir);
+    }
+
+    #[tokio::test]
+    async fn it_records_observations() {
+        let (dir, mut cache) = populated().await;
+        let location = cache.get("28273,US").await.unwrap().unwrap();
+        let conditions = CurrentConditions {
+            temp_c: 21.5,
+            temp_f: 70.7,
+            icon: "icon".to_string(),
+            source: crate::weather::Source::OpenMeteo,
+            code: 2,
+            condition: "Partly cloudy".to_string(),
+            is_day: true,
+            humidity: None,
+            wind_kph: None,
+            pressure_hpa: None,
+            precip_mm: None,
+            visibility_km: None,
+            derived: crate::weather::Derived::default(),
+            air_quality: None,
+        };
+
+        cache
+            .record(Some("28273,US"), &location, &conditions, Unit::C)
+            .await
+            .unwrap();
+        cache
+            .record(None, &location, &conditions, Unit::C)
+            .await
+            .unwrap();
+        sqlx::query(
+            "UPDATE observations SET observed_at = observed_at - 86400 WHERE region IS NULL;",
+        )
+        .execute(&mut cache.connection)
+        .await
+        .unwrap();
+
+        let observations = cache.observations(None).await.unwrap();
+
+        assert_eq!(observations.len(), 2);
+        assert_eq!(observations[0].region, None);
+        assert_eq!(observations[1].region, Some("28273,US".to_string()));
+        assert!((observations[1].temp - 21.5).abs() < f64::EPSILON);
+        assert_eq!(observations[1].unit, "celsius");
+        assert_eq!(observations[1].provider, "OpenMeteo");
+        assert_eq!(observations[1].condition, "Partly cloudy");
+        assert!(observations[1].observed_at.ends_with('Z'));
+
+        let recent = cache.observations(Some(3600)).await.unwrap();
+
+        assert_eq!(recent, observations[1..]);
+
+        assert_eq!(cache.prune_observations(3600).await.unwrap(), 1);
+        assert_eq!(cache.observations(None).await.unwrap(), recent);
+
+        drop(dir);
+    }
+
+    #[tokio::test]
+    async fn it_stores_archive() {
+        let (dir, mut cache) = populated().await;
+        let location = cache.get("28273,US").await.unwrap().unwrap();
+        let day = crate::history::Day {
+            date: "2024-01-01".to_string(),
+            weather_code: Some(61),
+            temp_max: Some(52.1),
+            temp_min: None,
+            temp_mean: Some(46.0),
+            precipitation: Some(0.4),
+        };
+        let hour = crate::history::Hour {
+            time: "2024-01-01T00:00".to_string(),
+            weather_code: Some(3),
+            temp: Some(44.6),
+            relative_humidity: Some(91.0),
+            precipitation: Some(0.0),
+        };
+        let archive = Archive {
+            days: vec![day.clone()],
+            hours: vec![hour],
+        };
+
+        cache
+            .store_archive(&location, Unit::F, &archive)
+            .await
+            .unwrap();
+        // fetching the same days again replaces them
+        cache
+            .store_archive(&location, Unit::F, &archive)
+            .await
+            .unwrap();
+
+        let (days, hours): (i64, i64) = sqlx::query_as(
+            "SELECT (SELECT COUNT(*) FROM daily), (SELECT COUNT(*) FROM hourly);",
+        )
+        .fetch_one(&mut cache.connection)
+        .await
+        .unwrap();
+        let (temp_max, temp_min, unit): (Option<f64>, Option<f64>, String) =
+            sqlx::query_as("SELECT temp_max, temp_min, unit FROM daily;")
+                .fetch_one(&mut cache.connection)
+                .await
+                .unwrap();
 
-        assert!(cache.get("00000").await.unwrap().is_none());
+        assert_eq!((days, hours), (1, 1));
+        assert_eq!(temp_max, day.temp_max);
+        assert_eq!(temp_min, None);
+        assert_eq!(unit, "fahrenheit");
 
         drop(dir);
     }
@@ -1,26 +1,109 @@
-use serde::Serialize;
+use std::{panic, sync::Arc};
+
+use serde::{Serialize, Serializer};
+use tokio::{sync::Semaphore, task};
 
 use crate::{
-    cache::Cache, config::Config, location, weather::CurrentConditions,
+    air_quality::{AirQuality, Pollen},
+    alerts::{self, Alert},
+    astro::Astro,
+    cache::Cache,
+    config::Config,
+    location::{self, Location},
+    rules::{Rule, Snapshot},
+    units::Units,
+    weather::CurrentConditions,
 };
 
-use crate::Unit;
+use crate::Result;
+
+/// Regions whose weather `Conditions::fetch_all` fetches at the same time.
+const CONCURRENT_FETCHES: usize = 4;
 
-#[derive(Debug, Serialize)]
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct Output {
-    pub temp: i32,
+    /// Rounded as set by `Config::rounding` and `Config::precision`, written
+    /// without decimals when it has none.
+    #[serde(serialize_with = "whole_if_integral")]
+    pub temp: f64,
+    /// The temperature before rounding.
+    pub temp_exact: f64,
     pub icon: String,
+    /// The unit of each quantity below.
+    pub units: Units,
+    /// Relative humidity, in percent.
+    pub humidity: Option<f64>,
+    pub wind_speed: Option<f64>,
+    /// Mean sea level pressure.
+    pub pressure: Option<f64>,
+    /// Precipitation over the last hour (`WeatherAPI`) or 15 minutes
+    /// (Open-Meteo).
+    pub precipitation: Option<f64>,
+    pub visibility: Option<f64>,
+    /// Dew point, in the temperature unit like the heat index, wind chill
+    /// and wet-bulb temperature. These are computed when the provider doesn't
+    /// report them, and are only `null` when it didn't report the humidity
+    /// (or wind speed for the wind chill) either.
+    pub dew_point: Option<f64>,
+    pub heat_index: Option<f64>,
+    pub wind_chill: Option<f64>,
+    pub wet_bulb: Option<f64>,
+    /// Canadian humidex, a unitless "feels like" index.
+    pub humidex: Option<f64>,
+    /// Wind force on the Beaufort scale, from 0 to 12.
+    pub beaufort: Option<u8>,
+    /// Present when `Config::show_location` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+    /// Present when `Config::show_air_quality` is set and the air quality
+    /// could be fetched.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub air_quality: Option<AirQuality>,
+    /// Present when `Config::show_pollen` is set and the pollen counts could
+    /// be fetched.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pollen: Option<Pollen>,
+    /// Present when `Config::show_alerts` is set and the alerts could be
+    /// fetched, empty when none are active.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alerts: Option<Vec<Alert>>,
+    /// Present when `Config::show_astro` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub astro: Option<Astro>,
+}
+
+/// Conditions fetched for a location, before they are turned into `Output`.
+struct Observed {
+    conditions: CurrentConditions,
+    location: Location,
+    air_quality: Option<AirQuality>,
+    pollen: Option<Pollen>,
+    alerts: Option<Vec<Alert>>,
+    astro: Option<Astro>,
 }
 
 pub struct Conditions {
     config: Config,
     region: Option<String>,
+    pick: Option<usize>,
 }
 
 impl Conditions {
+    /// Creates a new `Conditions` for `region`, or the configured/detected
+    /// location when `None`.
+    ///
+    /// `pick` chooses between several places matching a place name (1-based).
     #[must_use]
-    pub fn new(config: Config, region: Option<String>) -> Self {
-        Self { config, region }
+    pub fn new(
+        config: Config,
+        region: Option<String>,
+        pick: Option<usize>,
+    ) -> Self {
+        Self {
+            config,
+            region,
+            pick,
+        }
     }
 
     /// Fetches current weather conditions based on the provided configuration
@@ -34,47 +117,335 @@ impl Conditions {
     /// # Returns
     ///
     /// Returns a `Result` containing the fetched weather conditions as an
-    /// `Output` struct on success, or an `eyre::Error` on failure.
+    /// `Output` struct on success, or an `Error` on failure.
     ///
     /// # Errors
     ///
-    /// This function can return an `eyre::Error` if any of the following
+    /// This function can return an `Error` if any of the following
     /// conditions are met:
     ///
     /// * The location retrieval from the cache fails.
     /// * The location retrieval from the configuration fails.
     /// * The retrieval of current weather conditions fails.
-    pub async fn fetch(&mut self, cache: &mut Cache) -> eyre::Result<Output> {
-        let location = if let Some(region) = &self.region {
-            location::get(cache, Some(region)).await?
+    pub async fn fetch(&mut self, cache: &mut Cache) -> Result<Output> {
+        let location = self.locate(cache).await?;
+        let observed = self.observe(location)?;
+
+        self.record(cache, &observed).await;
+
+        Ok(self.output(observed))
+    }
+
+    /// Fetches current weather conditions for several regions at once.
+    ///
+    /// Locations are resolved one after another (they share the cache) and
+    /// then the weather is fetched on the blocking thread pool, for up to
+    /// `CONCURRENT_FETCHES` regions at a time. Every
+    /// `Output` has its location attached, regardless of
+    /// `Config::show_location`.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration shared by every region.
+    /// * `regions` - Regions in any form accepted by `location::get`.
+    /// * `cache` - A mutable reference to the cache object used for storing
+    ///   location data.
+    ///
+    /// # Returns
+    ///
+    /// One `Result` per region, in the order of `regions`, so that a failure
+    /// for one region does not hide the conditions for the others.
+    pub async fn fetch_all(
+        mut config: Config,
+        regions: Vec<String>,
+        cache: &mut Cache,
+    ) -> Vec<Result<Output>> {
+        config.show_location = true;
+
+        let mut located = Vec::with_capacity(regions.len());
+
+        for region in regions {
+            let conditions = Self::new(config.clone(), Some(region), None);
+            let location = conditions.locate(cache).await;
+
+            located.push((conditions, location));
+        }
+
+        let permits = Arc::new(Semaphore::new(CONCURRENT_FETCHES));
+        let mut handles = Vec::with_capacity(located.len());
+
+        for (conditions, location) in located {
+            // the semaphore is never closed
+            let permit = Arc::clone(&permits).acquire_owned().await.ok();
+
+            handles.push(task::spawn_blocking(move || {
+                let observed =
+                    location.and_then(|location| conditions.observe(location));
+
+                drop(permit);
+
+                (conditions, observed)
+            }));
+        }
+
+        let mut results = Vec::with_capacity(handles.len());
+
+        for handle in handles {
+            let (conditions, observed) = handle
+                .await
+                .unwrap_or_else(|err| panic::resume_unwind(err.into_panic()));
+
+            if let Ok(observed) = &observed {
+                conditions.record(cache, observed).await;
+            }
+
+            results.push(observed.map(|observed| conditions.output(observed)));
+        }
+
+        results
+    }
+
+    /// Fetches the current air quality for the region.
+    ///
+    /// # Errors
+    ///
+    /// This function can return an `Error` if the location can't be
+    /// retrieved or every air quality provider failed.
+    pub async fn air_quality(&self, cache: &mut Cache) -> Result<AirQuality> {
+        let location = self.locate(cache).await?;
+
+        AirQuality::get(&self.config, &location)
+    }
+
+    /// Fetches the current pollen counts for the region.
+    ///
+    /// # Errors
+    ///
+    /// This function can return an `Error` if the location can't be
+    /// retrieved or the pollen counts can't be fetched.
+    pub async fn pollen(&self, cache: &mut Cache) -> Result<Pollen> {
+        let location = self.locate(cache).await?;
+
+        Pollen::get(&self.config, &location)
+    }
+
+    /// Fetches the weather alerts active in the region.
+    ///
+    /// # Errors
+    ///
+    /// This function can return an `Error` if the location can't be
+    /// retrieved or every alert provider failed.
+    pub async fn alerts(&self, cache: &mut Cache) -> Result<Vec<Alert>> {
+        let location = self.locate(cache).await?;
+
+        alerts::get(&self.config, &location)
+    }
+
+    /// Computes the position of the sun and moon in the region now, offline.
+    ///
+    /// # Errors
+    ///
+    /// This function can return an `Error` if the location can't be
+    /// retrieved.
+    pub async fn astro(&self, cache: &mut Cache) -> Result<Astro> {
+        let location = self.locate(cache).await?;
+
+        Astro::now(&location)
+    }
+
+    /// Fetches the values `rules` are evaluated against for the region.
+    ///
+    /// # Errors
+    ///
+    /// This function can return an `Error` if the location can't be
+    /// retrieved, values that can't be fetched are left out.
+    pub async fn snapshot(
+        &self,
+        cache: &mut Cache,
+        rules: &[Rule],
+    ) -> Result<Snapshot> {
+        let location = self.locate(cache).await?;
+
+        Ok(Snapshot::get(&self.config, &location, rules))
+    }
+
+    async fn locate(&self, cache: &mut Cache) -> Result<Location> {
+        let mut location = if let Some(region) = &self.region {
+            location::get(&self.config, cache, Some(region), self.pick).await?
         } else {
             self.config.get_location(cache).await?
         };
 
-        let conditions = CurrentConditions::get(&self.config, &location)?;
-        let output = self.to_output(conditions);
+        if self.config.show_location {
+            location::describe(&self.config, cache, &mut location).await;
+        }
+
+        Ok(location)
+    }
+
+    fn observe(&self, location: Location) -> Result<Observed> {
+        let mut conditions = CurrentConditions::get(&self.config, &location)?;
+        // reported along with the conditions by some providers
+        let air_quality = self.config.show_air_quality.then(|| {
+            conditions.air_quality.take().or_else(|| {
+                optional(
+                    "air quality",
+                    AirQuality::get(&self.config, &location),
+                )
+            })
+        });
+        let pollen = self
+            .config
+            .show_pollen
+            .then(|| optional("pollen", Pollen::get(&self.config, &location)));
+        let alerts = self
+            .config
+            .show_alerts
+            .then(|| optional("alerts", alerts::get(&self.config, &location)));
+        let astro = self
+            .config
+            .show_astro
+            .then(|| optional("astronomy", Astro::now(&location)));
 
-        Ok(output)
+        Ok(Observed {
+            conditions,
+            location,
+            air_quality: air_quality.flatten(),
+            pollen: pollen.flatten(),
+            alerts: alerts.flatten(),
+            astro: astro.flatten(),
+        })
+    }
+
+    /// Adds the observation to the history, failing to do so only logs a
+    /// warning as the conditions were fetched all the same.
+    async fn record(&self, cache: &mut Cache, observed: &Observed) {
+        if let Err(err) = cache
+            .record(
+                self.region.as_deref(),
+                &observed.location,
+                &observed.conditions,
+                self.config.units().temperature,
+            )
+            .await
+        {
+            log::warn!("failed to record observation: {err}");
+        }
+
+        let retention_days = self.config.history_retention_days;
+
+        if retention_days > 0 {
+            if let Err(err) = cache
+                .prune_observations(retention_days.saturating_mul(86_400))
+                .await
+            {
+                log::warn!("failed to prune observations: {err}");
+            }
+        }
+    }
+
+    fn output(&self, observed: Observed) -> Output {
+        let mut output = self.to_output(observed.conditions);
+
+        if self.config.show_location {
+            output.location = Some(observed.location);
+        }
+
+        output.air_quality = observed.air_quality;
+        output.pollen = observed.pollen;
+        output.alerts = observed.alerts;
+        output.astro = observed.astro;
+
+        output
     }
 
     fn to_output(&self, conditions: CurrentConditions) -> Output {
-        let temp = match self.config.unit {
-            Unit::C => conditions.temp_c,
-            Unit::F => conditions.temp_f,
+        let units = self.config.units();
+
+        // decimals past the fourth are noise from the f32 conversion
+        let temp_exact = round(units.temperature(conditions.temp_c.into()), 4);
+        let temp = self
+            .config
+            .rounding
+            .round(temp_exact, self.config.precision);
+
+        let icon = conditions.icon;
+        let derived = conditions.derived;
+        let temperature = |celsius: Option<f32>| {
+            celsius.map(|celsius| round(units.temperature(celsius.into()), 1))
+        };
+        // inches and inches of mercury need the second decimal
+        let convert = |value: Option<f32>, to: fn(&Units, f64) -> f64| {
+            value.map(|value| round(to(&units, value.into()), 2))
         };
 
+        Output {
+            temp,
+            temp_exact,
+            icon,
+            units,
+            humidity: conditions
+                .humidity
+                .map(|humidity| round(humidity.into(), 1)),
+            wind_speed: convert(conditions.wind_kph, Units::wind),
+            pressure: convert(conditions.pressure_hpa, Units::pressure),
+            precipitation: convert(conditions.precip_mm, Units::precipitation),
+            visibility: convert(conditions.visibility_km, Units::visibility),
+            dew_point: temperature(derived.dew_point_c),
+            heat_index: temperature(derived.heat_index_c),
+            wind_chill: temperature(derived.wind_chill_c),
+            wet_bulb: temperature(derived.wet_bulb_c),
+            humidex: derived.humidex.map(|humidex| round(humidex.into(), 1)),
+            beaufort: derived.beaufort,
+            ..Default::default()
+        }
+    }
+}
+
+/// Rounds to `decimals`, finer precision than providers report in is noise.
+fn round(value: f64, decimals: i32) -> f64 {
+    let scale = 10_f64.powi(decimals);
+
+    (value * scale).round() / scale
+}
+
+/// Writes `72.0` as `72`, as `temp` was an integer before it could have
+/// decimals.
+// serde passes fields by reference
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn whole_if_integral<S: Serializer>(
+    value: &f64,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
         #[allow(clippy::cast_possible_truncation)]
-        let temp = temp as i32;
+        return serializer.serialize_i64(*value as i64);
+    }
 
-        let icon = conditions.icon;
+    serializer.serialize_f64(*value)
+}
 
-        Output { temp, icon }
+/// Unwraps data that's optional (in `Output` or a rules snapshot), failing to
+/// get it only logs a warning as the rest is usable all the same.
+pub(crate) fn optional<T>(what: &str, result: Result<T>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(err) => {
+            log::warn!("failed to get {what}: {err}");
+
+            None
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        units::Rounding,
+        weather::{Derived, Source},
+        Error, Unit,
+    };
 
     #[tokio::test]
     async fn test_conditions_to_output() {
@@ -86,11 +457,138 @@ mod tests {
             temp_c: 10.0,
             temp_f: 50.0,
             icon: "icon".to_string(),
+            source: Source::OpenMeteo,
+            code: 0,
+            condition: "Clear sky".to_string(),
+            is_day: true,
+            humidity: None,
+            wind_kph: None,
+            pressure_hpa: None,
+            precip_mm: None,
+            visibility_km: None,
+            derived: Derived::default(),
+            air_quality: None,
         };
 
-        let output = Conditions::new(config, None).to_output(conditions);
+        let output = Conditions::new(config, None, None).to_output(conditions);
 
-        assert_eq!(output.temp, 10);
+        assert!((output.temp - 10.0).abs() < f64::EPSILON);
         assert_eq!(output.icon, "icon");
     }
+
+    #[tokio::test]
+    async fn it_fetches_all_regions_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache =
+            Cache::new(Some(dir.path().join("test.db").display().to_string()))
+                .await
+                .unwrap();
+        let regions = vec![
+            "@home".to_string(),
+            "geo:north".to_string(),
+            "@office".to_string(),
+        ];
+
+        let results =
+            Conditions::fetch_all(Config::default(), regions, &mut cache).await;
+
+        assert_eq!(results.len(), 3);
+        assert!(matches!(
+            &results[0],
+            Err(Error::LocationNotFound(name)) if name == "@home"
+        ));
+        assert!(matches!(&results[1], Err(Error::InvalidLocation(_))));
+        assert!(matches!(
+            &results[2],
+            Err(Error::LocationNotFound(name)) if name == "@office"
+        ));
+    }
+
+    #[test]
+    fn it_rounds_temp_as_configured() {
+        let conditions = || CurrentConditions {
+            temp_c: 22.17,
+            temp_f: 71.9,
+            icon: "icon".to_string(),
+            source: Source::OpenMeteo,
+            code: 0,
+            condition: "Clear sky".to_string(),
+            is_day: true,
+            humidity: None,
+            wind_kph: None,
+            pressure_hpa: None,
+            precip_mm: None,
+            visibility_km: None,
+            derived: Derived::default(),
+            air_quality: None,
+        };
+        let output = |rounding, precision| {
+            let config = Config {
+                unit: Unit::F,
+                rounding,
+                precision,
+                ..Default::default()
+            };
+
+            Conditions::new(config, None, None).to_output(conditions())
+        };
+
+        let nearest = output(Rounding::Nearest, 0);
+        assert!((nearest.temp - 72.0).abs() < f64::EPSILON);
+        assert!((nearest.temp_exact - 71.906).abs() < f64::EPSILON);
+        assert_eq!(
+            serde_json::to_value(&nearest).unwrap()["temp"],
+            serde_json::json!(72)
+        );
+
+        let truncated = output(Rounding::Truncate, 0);
+        assert!((truncated.temp - 71.0).abs() < f64::EPSILON);
+
+        let precise = output(Rounding::Ceil, 1);
+        assert!((precise.temp - 72.0).abs() < f64::EPSILON);
+        assert_eq!(
+            serde_json::to_value(output(Rounding::Floor, 1)).unwrap()["temp"],
+            serde_json::json!(71.9)
+        );
+    }
+
+    #[test]
+    fn it_converts_derived_quantities_to_unit() {
+        let config = Config {
+            unit: Unit::F,
+            ..Default::default()
+        };
+        let conditions = CurrentConditions {
+            temp_c: 10.0,
+            temp_f: 50.0,
+            icon: "icon".to_string(),
+            source: Source::WeatherAPI,
+            code: 1000,
+            condition: "Sunny".to_string(),
+            is_day: true,
+            humidity: Some(71.0),
+            wind_kph: Some(13.0),
+            pressure_hpa: Some(1013.25),
+            precip_mm: Some(2.54),
+            visibility_km: None,
+            derived: Derived {
+                dew_point_c: Some(5.0),
+                humidex: Some(8.94),
+                beaufort: Some(3),
+                ..Default::default()
+            },
+            air_quality: None,
+        };
+
+        let output = Conditions::new(config, None, None).to_output(conditions);
+
+        assert_eq!(output.units.wind, crate::units::Wind::Mph);
+        assert_eq!(output.wind_speed, Some(8.08));
+        assert_eq!(output.pressure, Some(29.92));
+        assert_eq!(output.precipitation, Some(0.1));
+        assert_eq!(output.dew_point, Some(41.0));
+        assert_eq!(output.humidex, Some(8.9));
+        assert_eq!(output.wind_chill, None);
+        assert_eq!(output.beaufort, Some(3));
+    }
 }
@@ -1,29 +1,226 @@
-use std::fmt;
+use std::{collections::BTreeMap, fmt};
 
-use eyre::WrapErr;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::{cache::Cache, location, Unit};
+use crate::{
+    cache::Cache,
+    location,
+    rules::Rule,
+    units::{Rounding, Units, UnitsConfig},
+    webhook::Webhook,
+    Error, Result, Unit,
+};
 
 #[derive(Error, Debug)]
 pub enum ParseConfigError {
     #[error("failure to load configuration")]
-    Loading(#[from] confy::ConfyError),
+    Loading(#[source] confy::ConfyError),
+    #[error("error saving config")]
+    Storing(#[source] confy::ConfyError),
     #[error("configuration for {0} not found")]
     Missing(String),
+    #[error("error determining config path")]
+    Path,
+    #[error("invalid configuration: {0}")]
+    Invalid(String),
 }
 
 pub const APP_NAME: &str = "conditions";
 pub const CONFIG_NAME: &str = "config";
+/// Shortest time between evaluations of the rules in watch mode, so that
+/// watching doesn't hammer the providers.
+pub const MIN_WATCH_INTERVAL_SECS: u64 = 60;
 
-#[derive(Deserialize, Serialize, Debug, Default)]
+// one toggle per optional `Output` field
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Deserialize, Serialize, Debug)]
 #[serde(default)]
 pub struct Config {
     #[serde(flatten)]
     pub location: Option<location::Location>,
+    /// Temperature unit, picks the unit system unless `units` sets one.
     pub unit: Unit,
+    /// Unit system and per-quantity overrides, see `Config::units`.
+    #[serde(skip_serializing_if = "UnitsConfig::is_empty")]
+    pub units: UnitsConfig,
+    /// How the temperature in `Output::temp` is rounded.
+    pub rounding: Rounding,
+    /// Decimals kept in `Output::temp`.
+    pub precision: u8,
     pub weatherapi_token: Option<String>,
+    /// Include the location (with its name) in `Output`.
+    pub show_location: bool,
+    /// Include the air quality in `Output`.
+    pub show_air_quality: bool,
+    /// Include the pollen counts in `Output`.
+    pub show_pollen: bool,
+    /// Include the active weather alerts in `Output`.
+    pub show_alerts: bool,
+    /// Include the position of the sun and moon in `Output`.
+    pub show_astro: bool,
+    /// Name of the entry in `locations` to use when no region is given, takes
+    /// precedence over `location`.
+    pub default_location: Option<String>,
+    /// Where to locate the machine when no location is set, in order of
+    /// preference.
+    pub location_sources: Vec<location::LocationSource>,
+    /// Address of the gpsd daemon used by the `gpsd` location source.
+    pub gpsd_address: String,
+    /// IP geolocation services to try, in order, when no location is set.
+    pub ip_providers: Vec<location::IpProvider>,
+    /// Seconds the IP-derived location is reused for on the same network, 0
+    /// looks it up on every run.
+    pub ip_location_ttl_secs: u64,
+    /// Days observations are kept in the history for, 0 keeps them forever.
+    pub history_retention_days: u64,
+    /// Command run with `sh -c` when a rule fires in watch mode, the event is
+    /// written to its stdin as JSON.
+    pub hook: Option<String>,
+    /// Seconds between evaluations of the rules in watch mode, at least
+    /// `MIN_WATCH_INTERVAL_SECS`.
+    pub watch_interval_secs: u64,
+    /// Conditions to be notified about in watch mode.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<Rule>,
+    /// URLs the events of watch mode are sent to.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<Webhook>,
+    /// Saved locations by name, referenced as `@name` in place of a region.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub locations: BTreeMap<String, location::Location>,
+    pub network: Network,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            location: None,
+            unit: Unit::default(),
+            units: UnitsConfig::default(),
+            rounding: Rounding::default(),
+            precision: 0,
+            weatherapi_token: None,
+            show_location: false,
+            show_air_quality: false,
+            show_pollen: false,
+            show_alerts: false,
+            show_astro: false,
+            default_location: None,
+            location_sources: vec![location::LocationSource::Ip],
+            gpsd_address: "127.0.0.1:2947".to_string(),
+            ip_providers: location::IpProvider::all(),
+            ip_location_ttl_secs: 3_600,
+            history_retention_days: 365,
+            hook: None,
+            watch_interval_secs: 600,
+            rules: vec![],
+            webhooks: vec![],
+            locations: BTreeMap::new(),
+            network: Network::default(),
+        }
+    }
+}
+
+/// Timeouts and retry behaviour applied to HTTP requests made to a provider.
+///
+/// Transient failures (5xx responses, refused or reset connections and
+/// timeouts) are retried up to `retries` times. The delay between attempts
+/// grows exponentially from `backoff_ms` up to `max_backoff_ms` with random
+/// jitter applied.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RequestPolicy {
+    pub connect_timeout_ms: u64,
+    pub read_timeout_ms: u64,
+    pub retries: u32,
+    pub backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RequestPolicy {
+    fn default() -> Self {
+        Self {
+            connect_timeout_ms: 5_000,
+            read_timeout_ms: 10_000,
+            retries: 2,
+            backoff_ms: 250,
+            max_backoff_ms: 4_000,
+        }
+    }
+}
+
+/// Fields of a `RequestPolicy` set for a single provider, the others come from
+/// the `[network]` defaults.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PolicyOverride {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connect_timeout_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_timeout_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backoff_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_backoff_ms: Option<u64>,
+}
+
+impl PolicyOverride {
+    /// Applies the fields that are set on top of `defaults`.
+    #[must_use]
+    pub fn apply(&self, defaults: RequestPolicy) -> RequestPolicy {
+        RequestPolicy {
+            connect_timeout_ms: self
+                .connect_timeout_ms
+                .unwrap_or(defaults.connect_timeout_ms),
+            read_timeout_ms: self
+                .read_timeout_ms
+                .unwrap_or(defaults.read_timeout_ms),
+            retries: self.retries.unwrap_or(defaults.retries),
+            backoff_ms: self.backoff_ms.unwrap_or(defaults.backoff_ms),
+            max_backoff_ms: self
+                .max_backoff_ms
+                .unwrap_or(defaults.max_backoff_ms),
+        }
+    }
+}
+
+/// Network settings, a default `RequestPolicy` plus optional per-provider
+/// overrides keyed by provider name (`weatherapi`, `open_meteo`,
+/// `open_meteo_archive`, `open_meteo_air_quality`, `open_meteo_geocoding`,
+/// `nominatim`, `nws`, `ipinfo`, `ip_api`, `ipwho`, `ifconfig`, `gpsd`,
+/// `webhook`).
+///
+/// ```toml
+/// [network]
+/// retries = 3
+///
+/// [network.providers.weatherapi]
+/// read_timeout_ms = 2000
+/// retries = 0
+/// ```
+///
+/// A provider entry only overrides the fields it sets, the others keep the
+/// value from `[network]`.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Network {
+    #[serde(flatten)]
+    pub defaults: RequestPolicy,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub providers: BTreeMap<String, PolicyOverride>,
+}
+
+impl Network {
+    /// Returns the `RequestPolicy` to use for the named provider.
+    #[must_use]
+    pub fn policy(&self, provider: &str) -> RequestPolicy {
+        self.providers
+            .get(provider)
+            .map_or(self.defaults, |policy| policy.apply(self.defaults))
+    }
 }
 
 impl Config {
@@ -33,18 +230,50 @@ impl Config {
     ///
     /// # Errors
     ///
-    /// This function can return an error of type `eyre::Report` if there was an issue loading the configuration. The possible error scenarios include:
+    /// This function can return `Error::Config` if there was an issue loading the configuration. The possible error scenarios include:
     ///
     /// - If there was an error parsing the configuration file.
     /// - If there was an error reading the configuration file.
     /// - If the configuration file does not exist.
-    pub fn load() -> eyre::Result<Self> {
-        confy::load(APP_NAME, CONFIG_NAME)
-            .map_err(ParseConfigError::Loading)
-            .wrap_err("error loading config")
+    /// - If a setting is invalid, see `Config::validate`.
+    pub fn load() -> Result<Self> {
+        let config: Self = confy::load(APP_NAME, CONFIG_NAME)
+            .map_err(ParseConfigError::Loading)?;
+
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Checks the settings that deserialize fine but can't work.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseConfigError::Invalid` if `location_sources` or
+    /// `ip_providers` is empty.
+    pub fn validate(&self) -> std::result::Result<(), ParseConfigError> {
+        if self.location_sources.is_empty() {
+            return Err(ParseConfigError::Invalid(
+                "location_sources must list at least one source".to_string(),
+            ));
+        }
+
+        if self.ip_providers.is_empty() {
+            return Err(ParseConfigError::Invalid(
+                "ip_providers must list at least one provider".to_string(),
+            ));
+        }
+
+        if self.watch_interval_secs < MIN_WATCH_INTERVAL_SECS {
+            return Err(ParseConfigError::Invalid(format!(
+                "watch_interval_secs must be at least {MIN_WATCH_INTERVAL_SECS}"
+            )));
+        }
+
+        Ok(())
     }
 
-    fn path() -> eyre::Result<std::path::PathBuf> {
+    fn path() -> Result<std::path::PathBuf> {
         let path = confy::get_configuration_file_path(APP_NAME, CONFIG_NAME)
             .map_err(ParseConfigError::Loading)?;
 
@@ -55,10 +284,8 @@ impl Config {
     ///
     /// # Errors
     ///
-    /// Returns an [`eyre::Result`](https://docs.rs/eyre/latest/eyre/type.Result.html) that may contain the following errors:
-    ///
-    /// - If the path to the configuration file cannot be retrieved, an [`eyre::Report`](https://docs.rs/eyre/latest/eyre/struct.Report.html) is returned.
-    pub fn location() -> eyre::Result<String> {
+    /// Returns `Error::Config` if the path to the configuration file cannot be retrieved.
+    pub fn location() -> Result<String> {
         Ok(Config::path()?.display().to_string())
     }
 
@@ -75,10 +302,10 @@ impl Config {
     /// # Returns
     ///
     /// - `Ok(String)`: The path to the cache database file as a string.
-    pub fn cache_path() -> eyre::Result<String> {
+    pub fn cache_path() -> Result<String> {
         let mut path = Config::path()?
             .parent()
-            .ok_or(eyre::eyre!("error determinig config path"))?
+            .ok_or(ParseConfigError::Path)?
             .to_path_buf();
 
         path.push("cache.db");
@@ -90,12 +317,12 @@ impl Config {
     ///
     /// # Errors
     ///
-    /// Returns an `eyre::Result` that may contain an error if there was a problem loading the view.
+    /// Returns `Error::Config` if there was a problem loading the configuration.
     ///
     /// # Returns
     ///
     /// Returns a `String` that represents the current view.
-    pub fn view() -> eyre::Result<String> {
+    pub fn view() -> Result<String> {
         Ok(format!("{}", Self::load()?))
     }
 
@@ -107,35 +334,40 @@ impl Config {
     ///
     /// # Returns
     ///
-    /// Returns a `Result` containing the retrieved `Location` if successful, or an `eyre::Error` if an error occurs during the retrieval process.
+    /// Returns a `Result` containing the retrieved `Location` if successful, or an `Error` if an error occurs during the retrieval process.
     ///
     /// # Errors
     ///
-    /// This function can return an `eyre::Error` in the following scenarios:
+    /// This function can return an `Error` in the following scenarios:
     ///
+    /// * If the default location names a saved location that does not exist.
     /// * If the location is not already set and the retrieval process fails.
     pub async fn get_location(
-        &mut self,
+        &self,
         cache: &mut Cache,
-    ) -> eyre::Result<location::Location> {
-        match &self.location {
-            Some(location) => Ok(location.clone()),
-            None => {
-                let inferred = location::get(cache, None).await?;
+    ) -> Result<location::Location> {
+        if let Some(name) = &self.default_location {
+            return self.named_location(name);
+        }
 
-                Ok(inferred)
-            }
+        if let Some(location) = &self.location {
+            return Ok(location.clone());
         }
+
+        let inferred = location::get(self, cache, None, None).await?;
+
+        Ok(inferred)
     }
 
     /// Sets the location of the user.
     ///
-    /// This function is used to set the location of the user. It takes in the latitude and longitude coordinates as parameters and updates the user's location accordingly.
+    /// This function is used to set the location of the user. It looks up the provided region and stores the resulting location.
     ///
     /// # Arguments
     ///
-    /// * `latitude` - A f64 representing the latitude coordinate of the user's location.
-    /// * `longitude` - A f64 representing the longitude coordinate of the user's location.
+    /// * `cache` - A mutable reference to the cache object used for retrieving the location.
+    /// * `region` - A postal code and country or a place name, eg. `10001,US` or `Lisbon, PT`.
+    /// * `pick` - 1-based index choosing between several places matching a place name.
     ///
     /// # Returns
     ///
@@ -143,21 +375,32 @@ impl Config {
     ///
     /// # Errors
     ///
-    /// This function does not raise any errors.
+    /// Returns an `Error` if the region cannot be resolved to a single location or the configuration cannot be stored.
     pub async fn set_location(
         &mut self,
         cache: &mut Cache,
         region: &str,
-    ) -> eyre::Result<String> {
+        pick: Option<usize>,
+    ) -> Result<String> {
         let mut config = Self::load()?;
-        let location = location::get(cache, Some(region)).await?;
+        let mut location =
+            location::get(self, cache, Some(region), pick).await?;
 
-        config.location = Some(location);
+        location::describe(self, cache, &mut location).await;
+
+        config.replace_location(location);
         config.store()?;
 
         Ok("location stored successfully".to_string())
     }
 
+    /// Makes `location` the one used when no region is given, a default
+    /// saved location would otherwise take precedence over it.
+    fn replace_location(&mut self, location: location::Location) {
+        self.location = Some(location);
+        self.default_location = None;
+    }
+
     /// Unsets the location in the configuration file.
     ///
     /// This function unsets the location in the configuration file by setting it to `None`. It then saves the updated configuration file.
@@ -172,15 +415,134 @@ impl Config {
     /// # Returns
     ///
     /// This function returns a `Result` indicating whether the operation was successful or not. If successful, it returns a `String` with the message "location unset successfully".
-    pub fn unset_location() -> eyre::Result<String> {
+    pub fn unset_location() -> Result<String> {
         let mut config = Self::load()?;
 
         config.location = None;
+        config.default_location = None;
         config.store()?;
 
         Ok("location unset successfully".to_string())
     }
 
+    /// Retrieves a saved location by name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name the location was saved under, a leading `@` is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::LocationNotFound` if no location is saved under `name`.
+    pub fn named_location(&self, name: &str) -> Result<location::Location> {
+        let name = name.strip_prefix('@').unwrap_or(name);
+
+        self.locations
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::LocationNotFound(format!("@{name}")))
+    }
+
+    /// Looks up a region and saves the resulting location under `name`, replacing any location already saved under that name.
+    ///
+    /// # Arguments
+    ///
+    /// * `cache` - A mutable reference to the cache object used for retrieving the location.
+    /// * `name` - Name to save the location under, letters, digits, `-` and `_` only.
+    /// * `region` - A postal code and country, place name or `geo:` URI.
+    /// * `pick` - 1-based index choosing between several places matching a place name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if the name is invalid, the region cannot be resolved to a single location or the configuration cannot be stored.
+    pub async fn add_location(
+        &mut self,
+        cache: &mut Cache,
+        name: &str,
+        region: &str,
+        pick: Option<usize>,
+    ) -> Result<String> {
+        let name = validate_location_name(name)?;
+        let mut config = Self::load()?;
+        let mut location =
+            location::get(self, cache, Some(region), pick).await?;
+
+        location::describe(self, cache, &mut location).await;
+
+        config.locations.insert(name.to_string(), location);
+        config.store()?;
+
+        Ok(format!("location @{name} stored successfully"))
+    }
+
+    /// Removes the location saved under `name`, clearing the default location if it referred to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::LocationNotFound` if no location is saved under `name`, or `Error::Config` if the configuration cannot be loaded or stored.
+    pub fn remove_location(name: &str) -> Result<String> {
+        let name = name.strip_prefix('@').unwrap_or(name);
+        let mut config = Self::load()?;
+
+        if config.locations.remove(name).is_none() {
+            return Err(Error::LocationNotFound(format!("@{name}")));
+        }
+
+        if config.default_location.as_deref() == Some(name) {
+            config.default_location = None;
+        }
+
+        config.store()?;
+
+        Ok(format!("location @{name} removed successfully"))
+    }
+
+    /// Makes the location saved under `name` the one used when no region is given.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::LocationNotFound` if no location is saved under `name`, or `Error::Config` if the configuration cannot be loaded or stored.
+    pub fn set_default_location(name: &str) -> Result<String> {
+        let name = name.strip_prefix('@').unwrap_or(name);
+        let mut config = Self::load()?;
+
+        config.named_location(name)?;
+        config.default_location = Some(name.to_string());
+        config.store()?;
+
+        Ok(format!("default location set to @{name}"))
+    }
+
+    /// Lists the saved locations, one per line, marking the default with `*`.
+    #[must_use]
+    pub fn list_locations(&self) -> String {
+        if self.locations.is_empty() {
+            return "no saved locations".to_string();
+        }
+
+        self.locations
+            .iter()
+            .map(|(name, location)| {
+                let marker = if self.default_location.as_deref() == Some(name) {
+                    '*'
+                } else {
+                    ' '
+                };
+                let label = location.name.as_deref().unwrap_or(&location.loc);
+
+                format!("{marker} @{name}: {label}")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The unit of each quantity, from the unit system and overrides in
+    /// `units`, or from `unit` when no system is set.
+    #[must_use]
+    pub fn units(&self) -> Units {
+        self.units.resolve(self.unit)
+    }
+
     /// Sets the unit for the configuration and stores it.
     ///
     /// # Arguments
@@ -193,11 +555,17 @@ impl Config {
     ///
     /// # Errors
     ///
-    /// Returns an `eyre::Result` if there was an error loading or storing the configuration.
-    pub fn set_unit(unit: Unit) -> eyre::Result<String> {
+    /// Returns `Error::Config` if there was an error loading or storing the configuration.
+    pub fn set_unit(unit: Unit) -> Result<String> {
         let mut config = Self::load()?;
 
         config.unit = unit;
+
+        // the unit system would take precedence otherwise
+        if config.units.system.is_some() {
+            config.units.temperature = Some(unit);
+        }
+
         config.store()?;
 
         Ok(format!("unit stored as: {unit}"))
@@ -205,21 +573,23 @@ impl Config {
 
     /// Retrieves the `WeatherAPI` token.
     ///
-    /// This function returns the `WeatherAPI` token as a `Result<String, eyre::Report>`. If the token is present, it is returned as `Ok(token)`. If the token is missing, an error is returned as `Err(ParseConfigError::Missing("weatherapi key".to_owned())).wrap_err("error getting api key")`.
+    /// This function returns the `WeatherAPI` token as a `Result<String>`. If the token is present, it is returned as `Ok(token)`. If the token is missing, an error is returned as `Err(ParseConfigError::Missing("weatherapi key".to_owned()).into())`.
     ///
     /// # Returns
     ///
     /// - `Ok(token)`: The `WeatherAPI` token as a `String`.
-    /// - `Err(error)`: An error of type `eyre::Report` indicating the reason for failure.
+    /// - `Err(error)`: An `Error::Config` indicating the reason for failure.
     ///
     /// # Errors
     ///
-    /// Returns an `eyre::Result` if the weather api key is missing.
-    pub fn get_weatherapi_token(&self) -> eyre::Result<String> {
+    /// Returns `Error::Config` if the weather api key is missing.
+    pub fn get_weatherapi_token(&self) -> Result<String> {
         match &self.weatherapi_token {
             Some(key) => Ok(key.clone()),
-            None => Err(ParseConfigError::Missing("weatherapi key".to_owned()))
-                .wrap_err("error getting api key"),
+            None => {
+                Err(ParseConfigError::Missing("weatherapi key".to_owned())
+                    .into())
+            }
         }
     }
 
@@ -239,7 +609,7 @@ impl Config {
     ///
     /// * Loading the configuration file.
     /// * Storing the updated configuration file.
-    pub fn set_weatherapi_token(key: &str) -> eyre::Result<String> {
+    pub fn set_weatherapi_token(key: &str) -> Result<String> {
         let mut config = Self::load()?;
 
         config.weatherapi_token = Some(key.to_owned());
@@ -259,7 +629,7 @@ impl Config {
     /// Returns a `Result` indicating whether the weatherapi token was unset successfully or not.
     ///
     /// If successful, returns a `String` with the message "weatherapi.com key unset successfully".
-    pub fn unset_weatherapi_token() -> eyre::Result<String> {
+    pub fn unset_weatherapi_token() -> Result<String> {
         let mut config = Self::load()?;
 
         config.weatherapi_token = None;
@@ -272,10 +642,28 @@ impl Config {
     ///
     /// # Errors
     ///
-    /// Returns an `eyre::Result` that wraps an error if there was a problem saving the configuration.
-    pub fn store(&self) -> eyre::Result<()> {
+    /// Returns `Error::Config` if there was a problem saving the configuration.
+    pub fn store(&self) -> Result<()> {
         confy::store(APP_NAME, CONFIG_NAME, self)
-            .wrap_err("error saving config")
+            .map_err(ParseConfigError::Storing)?;
+
+        Ok(())
+    }
+}
+
+fn validate_location_name(name: &str) -> Result<&str> {
+    let name = name.strip_prefix('@').unwrap_or(name);
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+
+    if valid {
+        Ok(name)
+    } else {
+        Err(Error::InvalidLocation(format!(
+            "invalid location name \"{name}\", use letters, digits, '-' and '_'"
+        )))
     }
 }
 
@@ -285,11 +673,180 @@ impl fmt::Display for Config {
 
         write!(
             fmt,
-            "Stored Configuration\n  Coordinates: {}\n  Postal Code: {}\n  Unit: {}\n  Weather API Key: {}",
+            "Stored Configuration\n  Name: {}\n  Coordinates: {}\n  Postal Code: {}\n  Default Location: {}\n  Saved Locations: {}\n  Units: {}\n  Weather API Key: {}",
+            location.name.unwrap_or_default(),
             location.loc.clone(),
             location.postal_code.clone(),
-            self.unit,
+            self.default_location
+                .as_ref()
+                .map(|name| format!("@{name}"))
+                .unwrap_or_default(),
+            self.locations
+                .keys()
+                .map(|name| format!("@{name}"))
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.units(),
             self.weatherapi_token.clone().unwrap_or_default()
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_uses_provider_policy_when_present() {
+        let weatherapi = PolicyOverride {
+            retries: Some(0),
+            ..Default::default()
+        };
+        let network = Network {
+            defaults: RequestPolicy {
+                retries: 5,
+                ..Default::default()
+            },
+            providers: BTreeMap::from([("weatherapi".to_string(), weatherapi)]),
+        };
+
+        assert_eq!(network.policy("weatherapi").retries, 0);
+        assert_eq!(network.policy("open_meteo").retries, 5);
+    }
+
+    #[test]
+    fn it_inherits_unset_provider_fields_from_defaults() {
+        let network: Network = serde_json::from_str(
+            r#"{
+                "read_timeout_ms": 3000,
+                "retries": 4,
+                "providers": {"weatherapi": {"retries": 0}}
+            }"#,
+        )
+        .unwrap();
+
+        let policy = network.policy("weatherapi");
+
+        assert_eq!(policy.retries, 0);
+        assert_eq!(policy.read_timeout_ms, 3_000);
+        assert_eq!(
+            policy.connect_timeout_ms,
+            RequestPolicy::default().connect_timeout_ms
+        );
+    }
+
+    fn with_locations() -> Config {
+        let office = location::Location {
+            loc: "35.15,-80.81".to_string(),
+            name: Some("Charlotte, North Carolina, US".to_string()),
+            ..Default::default()
+        };
+        let home = location::Location {
+            loc: "35.01,-80.91".to_string(),
+            ..Default::default()
+        };
+
+        Config {
+            default_location: Some("office".to_string()),
+            locations: BTreeMap::from([
+                ("home".to_string(), home),
+                ("office".to_string(), office),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_finds_named_locations() {
+        let config = with_locations();
+
+        assert_eq!(config.named_location("@home").unwrap().loc, "35.01,-80.91");
+        assert_eq!(config.named_location("home").unwrap().loc, "35.01,-80.91");
+        assert!(matches!(
+            config.named_location("@parents"),
+            Err(Error::LocationNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_uses_location_set_after_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache =
+            Cache::new(Some(dir.path().join("test.db").display().to_string()))
+                .await
+                .unwrap();
+        let mut config = with_locations();
+        let location = location::Location {
+            loc: "40.75,-73.99".to_string(),
+            ..Default::default()
+        };
+
+        config.replace_location(location.clone());
+
+        assert_eq!(config.default_location, None);
+        assert_eq!(config.get_location(&mut cache).await.unwrap(), location);
+    }
+
+    #[test]
+    fn it_rejects_empty_ip_providers() {
+        let config = Config {
+            ip_providers: vec![],
+            ..Default::default()
+        };
+
+        assert!(Config::default().validate().is_ok());
+        assert!(matches!(
+            config.validate(),
+            Err(ParseConfigError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn it_rejects_empty_location_sources() {
+        let config = Config {
+            location_sources: vec![],
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            config.validate(),
+            Err(ParseConfigError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn it_rejects_short_watch_intervals() {
+        let config = Config {
+            watch_interval_secs: 0,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            config.validate(),
+            Err(ParseConfigError::Invalid(_))
+        ));
+        assert!(Config {
+            watch_interval_secs: MIN_WATCH_INTERVAL_SECS,
+            ..Default::default()
+        }
+        .validate()
+        .is_ok());
+    }
+
+    #[test]
+    fn it_lists_locations() {
+        assert_eq!(
+            with_locations().list_locations(),
+            "  @home: 35.01,-80.91\n* @office: Charlotte, North Carolina, US"
+        );
+        assert_eq!(Config::default().list_locations(), "no saved locations");
+    }
+
+    #[test]
+    fn it_validates_location_names() {
+        assert_eq!(validate_location_name("@office").unwrap(), "office");
+        assert_eq!(validate_location_name("my-home_2").unwrap(), "my-home_2");
+        assert!(validate_location_name("").is_err());
+        assert!(validate_location_name("my office").is_err());
+    }
+}
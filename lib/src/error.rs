@@ -0,0 +1,142 @@
+use thiserror::Error;
+
+use crate::config::ParseConfigError;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors returned from the public API of this crate.
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The provider could not be reached or responded with an unexpected
+    /// status, `status` is `None` when no response was received at all.
+    #[error("request to {provider} failed{}", status.map(|s| format!(" with status {s}")).unwrap_or_default())]
+    Network {
+        provider: String,
+        status: Option<u16>,
+    },
+    #[error("error parsing response from {provider}: {message}")]
+    Parse { provider: String, message: String },
+    #[error("rate limited by {provider}")]
+    RateLimited { provider: String },
+    #[error("invalid api key for {provider}")]
+    InvalidApiKey { provider: String },
+    /// The provider lacks the configuration (eg. an api key) needed to use it.
+    #[error("{provider} is not configured")]
+    Unavailable { provider: String },
+    #[error("{0}")]
+    InvalidLocation(String),
+    #[error("location not found: {0}")]
+    LocationNotFound(String),
+    #[error("{0}")]
+    InvalidDate(String),
+    /// Several places match a search, `candidates` are listed in the order
+    /// used to pick one.
+    #[error("\"{query}\" matches several places: {}", numbered(candidates))]
+    AmbiguousLocation {
+        query: String,
+        candidates: Vec<String>,
+    },
+    #[error("no providers succeeded: {}", join(.0))]
+    AllProvidersFailed(Vec<Error>),
+    /// The location of the machine could not be determined, holding the
+    /// error from each location source or ip geolocation provider tried.
+    #[error("unable to determine the location: {}", join(.0))]
+    LocationUnavailable(Vec<Error>),
+    #[error(transparent)]
+    Config(#[from] ParseConfigError),
+    #[error("cache error: {0}")]
+    Cache(#[from] sqlx::Error),
+    #[error("hook \"{command}\" failed: {message}")]
+    Hook { command: String, message: String },
+    #[error("error reading {path}: {source}")]
+    Read {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+impl From<std::convert::Infallible> for Error {
+    fn from(err: std::convert::Infallible) -> Self {
+        match err {}
+    }
+}
+
+impl Error {
+    /// Maps a failed HTTP request to `provider` onto the matching variant.
+    pub(crate) fn from_ureq(provider: &str, err: &ureq::Error) -> Self {
+        let provider = provider.to_string();
+
+        match err {
+            ureq::Error::Status(401 | 403, _) => {
+                Self::InvalidApiKey { provider }
+            }
+            ureq::Error::Status(429, _) => Self::RateLimited { provider },
+            ureq::Error::Status(status, _) => Self::Network {
+                provider,
+                status: Some(*status),
+            },
+            ureq::Error::Transport(_) => Self::Network {
+                provider,
+                status: None,
+            },
+        }
+    }
+}
+
+fn numbered(candidates: &[String]) -> String {
+    candidates
+        .iter()
+        .enumerate()
+        .map(|(i, candidate)| format!("{}) {candidate}", i + 1))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn join(errors: &[Error]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_displays_network_errors() {
+        let with_status = Error::Network {
+            provider: "weatherapi".to_string(),
+            status: Some(500),
+        };
+        let without_status = Error::Network {
+            provider: "weatherapi".to_string(),
+            status: None,
+        };
+
+        assert_eq!(
+            with_status.to_string(),
+            "request to weatherapi failed with status 500"
+        );
+        assert_eq!(without_status.to_string(), "request to weatherapi failed");
+    }
+
+    #[test]
+    fn it_lists_provider_failures() {
+        let error = Error::AllProvidersFailed(vec![
+            Error::InvalidApiKey {
+                provider: "weatherapi".to_string(),
+            },
+            Error::RateLimited {
+                provider: "open_meteo".to_string(),
+            },
+        ]);
+
+        assert_eq!(
+            error.to_string(),
+            "no providers succeeded: invalid api key for weatherapi, rate limited by open_meteo"
+        );
+    }
+}
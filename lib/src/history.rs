@@ -0,0 +1,412 @@
+use serde::Serialize;
+use sqlx::FromRow;
+
+use crate::{
+    api::Fetchable, cache::Cache, config::Config, location::Location,
+    units::Units, weather::open_meteo_archive, Error, Result, Unit,
+};
+
+/// A reading recorded in the cache database on every successful fetch.
+#[derive(Clone, Debug, PartialEq, Serialize, FromRow)]
+pub struct Observation {
+    /// UTC timestamp, eg. `2024-06-01T14:05:00Z`.
+    pub observed_at: String,
+    /// The region as requested, `None` for the configured or detected
+    /// location.
+    pub region: Option<String>,
+    pub name: Option<String>,
+    pub latitude: String,
+    pub longitude: String,
+    pub postal_code: String,
+    /// The weather provider that reported the reading.
+    pub provider: String,
+    /// Temperature in `unit`, the configured unit when listed with
+    /// `observations`.
+    pub temp: f64,
+    pub unit: String,
+    pub condition_code: i64,
+    pub condition: String,
+    pub is_day: bool,
+    pub icon: String,
+}
+
+/// Daily and hourly records fetched from the Open-Meteo archive.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Archive {
+    pub days: Vec<Day>,
+    pub hours: Vec<Hour>,
+}
+
+/// Summary of a day in the archive, temperatures and precipitation are in
+/// the configured units. Values are `None` where the archive has no data.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Day {
+    /// Local date, eg. `2024-01-01`.
+    pub date: String,
+    /// WMO weather interpretation code.
+    pub weather_code: Option<i32>,
+    pub temp_max: Option<f64>,
+    pub temp_min: Option<f64>,
+    pub temp_mean: Option<f64>,
+    pub precipitation: Option<f64>,
+}
+
+/// An hour in the archive, see `Day` for units.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Hour {
+    /// Local time, eg. `2024-01-01T13:00`.
+    pub time: String,
+    pub weather_code: Option<i32>,
+    pub temp: Option<f64>,
+    /// Percent.
+    pub relative_humidity: Option<f64>,
+    pub precipitation: Option<f64>,
+}
+
+/// Lists the observations recorded within the last `since_secs` seconds, all
+/// of them when `None`, with temperatures converted to the configured unit
+/// whatever unit was active when they were recorded.
+///
+/// # Errors
+///
+/// Any error reading the observations from the cache database.
+pub async fn observations(
+    config: &Config,
+    cache: &mut Cache,
+    since_secs: Option<u64>,
+) -> Result<Vec<Observation>> {
+    let units = config.units();
+    let observations = cache.observations(since_secs).await?;
+
+    Ok(observations
+        .into_iter()
+        .map(|observation| observation.convert(units))
+        .collect())
+}
+
+impl Observation {
+    /// Converts `temp` from the unit it was recorded in to `units`, leaving
+    /// readings in an unknown unit as they are.
+    fn convert(self, units: Units) -> Self {
+        let celsius = match self.unit.as_str() {
+            "celsius" => self.temp,
+            "fahrenheit" => (self.temp - 32.0) * 5.0 / 9.0,
+            _ => return self,
+        };
+
+        if self.unit == units.temperature.to_string() {
+            return self;
+        }
+
+        Self {
+            // providers report tenths of a degree
+            temp: (units.temperature(celsius) * 10.0).round() / 10.0,
+            unit: units.temperature.to_string(),
+            ..self
+        }
+    }
+}
+
+/// Fetches the daily and hourly records for `location` from `from` to `to`
+/// (inclusive, `YYYY-MM-DD`) from the Open-Meteo archive and stores them in
+/// the cache database, replacing records already stored for those days.
+///
+/// # Errors
+///
+/// - `Error::InvalidDate` if a date is malformed or `from` is after `to`.
+/// - Any error fetching the archive or storing the records.
+pub async fn backfill(
+    config: &Config,
+    cache: &mut Cache,
+    location: &Location,
+    from: &str,
+    to: &str,
+) -> Result<Archive> {
+    for date in [from, to] {
+        if !is_date(date) {
+            return Err(Error::InvalidDate(format!(
+                "invalid date \"{date}\", expect YYYY-MM-DD"
+            )));
+        }
+    }
+
+    // ISO 8601 dates sort chronologically
+    if from > to {
+        return Err(Error::InvalidDate(format!("{from} is after {to}")));
+    }
+
+    let archive =
+        open_meteo_archive::Client::new(config, location, from, to).fetch()?;
+
+    // stored as reported, the temperature unit is recorded along with them
+    cache.store_archive(location, Unit::C, &archive).await?;
+
+    Ok(archive.convert(config.units()))
+}
+
+impl Archive {
+    /// Converts the temperatures and precipitation, reported in °C and mm, to
+    /// `units`.
+    fn convert(self, units: Units) -> Self {
+        let temperature =
+            |temp: Option<f64>| temp.map(|temp| units.temperature(temp));
+        let precipitation = |precipitation: Option<f64>| {
+            precipitation
+                .map(|precipitation| units.precipitation(precipitation))
+        };
+
+        Self {
+            days: self
+                .days
+                .into_iter()
+                .map(|day| Day {
+                    temp_max: temperature(day.temp_max),
+                    temp_min: temperature(day.temp_min),
+                    temp_mean: temperature(day.temp_mean),
+                    precipitation: precipitation(day.precipitation),
+                    ..day
+                })
+                .collect(),
+            hours: self
+                .hours
+                .into_iter()
+                .map(|hour| Hour {
+                    temp: temperature(hour.temp),
+                    precipitation: precipitation(hour.precipitation),
+                    ..hour
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Checks that `date` is a valid `YYYY-MM-DD` calendar date.
+#[must_use]
+pub fn is_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('-').collect();
+
+    let [year, month, day] = parts.as_slice() else {
+        return false;
+    };
+
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return false;
+    }
+
+    let (Ok(year), Ok(month), Ok(day)) = (
+        year.parse::<u32>(),
+        month.parse::<u32>(),
+        day.parse::<u32>(),
+    ) else {
+        return false;
+    };
+
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+
+    (1..=days).contains(&day)
+}
+
+/// Column names of `to_csv`, in order.
+const CSV_HEADER: [&str; 13] = [
+    "observed_at",
+    "region",
+    "name",
+    "latitude",
+    "longitude",
+    "postal_code",
+    "provider",
+    "temp",
+    "unit",
+    "condition_code",
+    "condition",
+    "is_day",
+    "icon",
+];
+
+/// Formats observations as CSV (RFC 4180) with a header row.
+#[must_use]
+pub fn to_csv(observations: &[Observation]) -> String {
+    let mut lines = vec![CSV_HEADER.join(",")];
+
+    for observation in observations {
+        let fields = [
+            observation.observed_at.clone(),
+            observation.region.clone().unwrap_or_default(),
+            observation.name.clone().unwrap_or_default(),
+            observation.latitude.clone(),
+            observation.longitude.clone(),
+            observation.postal_code.clone(),
+            observation.provider.clone(),
+            observation.temp.to_string(),
+            observation.unit.clone(),
+            observation.condition_code.to_string(),
+            observation.condition.clone(),
+            observation.is_day.to_string(),
+            observation.icon.clone(),
+        ];
+
+        lines.push(
+            fields
+                .iter()
+                .map(|field| escape(field))
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+    }
+
+    lines.join("\n")
+}
+
+/// Quotes a CSV field when it contains a separator, quote or line break.
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::System;
+
+    #[test]
+    fn it_validates_dates() {
+        for date in ["2024-01-01", "2024-02-29", "2000-02-29", "1999-12-31"] {
+            assert!(is_date(date), "{date}");
+        }
+
+        for date in [
+            "2023-02-29",
+            "1900-02-29",
+            "2024-13-01",
+            "2024-04-31",
+            "2024-1-01",
+            "24-01-01",
+            "2024/01/01",
+            "2024-01-01T00:00",
+            "",
+        ] {
+            assert!(!is_date(date), "{date}");
+        }
+    }
+
+    #[tokio::test]
+    async fn it_rejects_invalid_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache =
+            Cache::new(Some(dir.path().join("test.db").display().to_string()))
+                .await
+                .unwrap();
+
+        for (from, to) in [("2024-01-31", "2024-01-01"), ("2024-01-01", "soon")]
+        {
+            assert!(matches!(
+                backfill(
+                    &Config::default(),
+                    &mut cache,
+                    &Location::default(),
+                    from,
+                    to
+                )
+                .await,
+                Err(Error::InvalidDate(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn it_converts_archive_to_units() {
+        let archive = Archive {
+            days: vec![Day {
+                date: "2024-01-01".to_string(),
+                weather_code: Some(61),
+                temp_max: Some(10.0),
+                temp_min: None,
+                temp_mean: Some(-40.0),
+                precipitation: Some(25.4),
+            }],
+            hours: vec![Hour {
+                time: "2024-01-01T13:00".to_string(),
+                weather_code: None,
+                temp: Some(100.0),
+                relative_humidity: Some(80.0),
+                precipitation: Some(0.0),
+            }],
+        };
+
+        let archive = archive.convert(System::Imperial.units());
+
+        assert_eq!(archive.days[0].temp_max, Some(50.0));
+        assert_eq!(archive.days[0].temp_min, None);
+        assert_eq!(archive.days[0].temp_mean, Some(-40.0));
+        assert_eq!(archive.days[0].precipitation, Some(1.0));
+        assert_eq!(archive.hours[0].temp, Some(212.0));
+        assert_eq!(archive.hours[0].relative_humidity, Some(80.0));
+    }
+
+    #[test]
+    fn it_converts_observations_to_unit() {
+        let observation = Observation {
+            observed_at: "2024-06-01T14:05:00Z".to_string(),
+            region: None,
+            name: None,
+            latitude: "35.1287".to_string(),
+            longitude: "-80.9338".to_string(),
+            postal_code: "28273".to_string(),
+            provider: "OpenMeteo".to_string(),
+            temp: 21.5,
+            unit: "celsius".to_string(),
+            condition_code: 2,
+            condition: "Partly cloudy".to_string(),
+            is_day: true,
+            icon: "icon".to_string(),
+        };
+
+        let fahrenheit = observation.clone().convert(System::Imperial.units());
+
+        assert!((fahrenheit.temp - 70.7).abs() < f64::EPSILON);
+        assert_eq!(fahrenheit.unit, "fahrenheit");
+
+        let celsius = fahrenheit.convert(System::Metric.units());
+
+        assert_eq!(celsius, observation);
+        assert_eq!(
+            observation.clone().convert(System::Metric.units()),
+            observation
+        );
+    }
+
+    #[test]
+    fn it_formats_csv() {
+        let observation = Observation {
+            observed_at: "2024-06-01T14:05:00Z".to_string(),
+            region: None,
+            name: Some("Charlotte, North Carolina, US".to_string()),
+            latitude: "35.1287".to_string(),
+            longitude: "-80.9338".to_string(),
+            postal_code: "28273".to_string(),
+            provider: "OpenMeteo".to_string(),
+            temp: 71.5,
+            unit: "fahrenheit".to_string(),
+            condition_code: 2,
+            condition: "Partly \"cloudy\"".to_string(),
+            is_day: true,
+            icon: "icon".to_string(),
+        };
+
+        assert_eq!(
+            to_csv(&[observation]),
+            "observed_at,region,name,latitude,longitude,postal_code,provider,temp,unit,condition_code,condition,is_day,icon\n\
+             2024-06-01T14:05:00Z,,\"Charlotte, North Carolina, US\",35.1287,-80.9338,28273,OpenMeteo,71.5,fahrenheit,2,\"Partly \"\"cloudy\"\"\",true,icon"
+        );
+    }
+}
@@ -1,7 +1,6 @@
-use lazy_static::lazy_static;
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::LazyLock};
 
-use crate::weather::Source;
+use crate::{astro, weather::Source};
 
 #[derive(Debug, PartialEq)]
 pub enum TimeOfDay {
@@ -19,6 +18,37 @@ impl From<u8> for TimeOfDay {
 }
 
 impl TimeOfDay {
+    /// Day while the sun is above the horizon at the coordinates at `time`
+    /// (Unix seconds).
+    #[must_use]
+    pub fn at(latitude: f64, longitude: f64, time: i64) -> Self {
+        if astro::solar_elevation(latitude, longitude, time)
+            > astro::SUNRISE_ALTITUDE
+        {
+            TimeOfDay::Day
+        } else {
+            TimeOfDay::Night
+        }
+    }
+
+    /// Uses `is_day` as reported by a provider, or derives it from the
+    /// position of the sun at `time` (Unix seconds) when the provider omitted
+    /// it. Day when the coordinates are missing too.
+    pub(crate) fn reported(
+        is_day: Option<u8>,
+        latitude: Option<f64>,
+        longitude: Option<f64>,
+        time: i64,
+    ) -> Self {
+        match (is_day, latitude, longitude) {
+            (Some(is_day), _, _) => TimeOfDay::from(is_day),
+            (None, Some(latitude), Some(longitude)) => {
+                TimeOfDay::at(latitude, longitude, time)
+            }
+            _ => TimeOfDay::Day,
+        }
+    }
+
     #[must_use]
     pub fn icon(&self, provider: &crate::weather::Source, code: i32) -> String {
         let icons: &HashMap<i32, &'static str> = match provider {
@@ -36,8 +66,8 @@ impl TimeOfDay {
     }
 }
 
-lazy_static! {
-    static ref WEATHERAPI_DAY_ICONS: HashMap<i32, &'static str> = {
+static WEATHERAPI_DAY_ICONS: LazyLock<HashMap<i32, &'static str>> =
+    LazyLock::new(|| {
         let mut m = HashMap::new();
         m.insert(1000, " "); // Clear/113
         m.insert(1003, " "); // Partly cloudy/116
@@ -88,9 +118,10 @@ lazy_static! {
         m.insert(1279, " "); // Patchy light snow with thunder/392
         m.insert(1282, " "); // Moderate or heavy snow with thunder/395
         m
-    };
+    });
 
-    static ref WEATHERAPI_NIGHT_ICONS: HashMap<i32, &'static str> = {
+static WEATHERAPI_NIGHT_ICONS: LazyLock<HashMap<i32, &'static str>> =
+    LazyLock::new(|| {
         let mut m = HashMap::new();
         m.insert(1000, ""); // Clear/113
         m.insert(1003, ""); // Partly cloudy/116
@@ -141,9 +172,10 @@ lazy_static! {
         m.insert(1279, ""); // Patchy light snow with thunder/392
         m.insert(1282, ""); // Moderate or heavy snow with thunder/395
         m
-    };
+    });
 
-    static ref OPEN_METEO_DAY_ICONS: HashMap<i32, &'static str> = {
+static OPEN_METEO_DAY_ICONS: LazyLock<HashMap<i32, &'static str>> =
+    LazyLock::new(|| {
         let mut m = HashMap::new();
         m.insert(0, " "); // Clear sky
         m.insert(1, " ");
@@ -174,9 +206,10 @@ lazy_static! {
         m.insert(96, " ");
         m.insert(99, " "); // Thunderstorm with slight and heavy hail
         m
-    };
+    });
 
-    static ref OPEN_METEO_NIGHT_ICONS: HashMap<i32, &'static str> = {
+static OPEN_METEO_NIGHT_ICONS: LazyLock<HashMap<i32, &'static str>> =
+    LazyLock::new(|| {
         let mut m = HashMap::new();
         m.insert(0, ""); // Clear sky
         m.insert(1, "");
@@ -207,8 +240,7 @@ lazy_static! {
         m.insert(96, "");
         m.insert(99, ""); // Thunderstorm with slight and heavy hail
         m
-    };
-}
+    });
 
 #[cfg(test)]
 mod tests {
@@ -229,6 +261,35 @@ mod tests {
         assert_eq!(TimeOfDay::from(42), TimeOfDay::Night);
     }
 
+    #[test]
+    fn it_derives_time_of_day_from_the_sun() {
+        // Charlotte on 2024-06-21 at 16:00Z and 06:00Z
+        assert_eq!(TimeOfDay::at(35.23, -80.84, 1_718_985_600), TimeOfDay::Day);
+        assert_eq!(
+            TimeOfDay::at(35.23, -80.84, 1_718_949_600),
+            TimeOfDay::Night
+        );
+    }
+
+    #[test]
+    fn it_prefers_reported_time_of_day() {
+        // Charlotte on 2024-06-21 at 16:00Z
+        let noon = 1_718_985_600;
+
+        assert_eq!(
+            TimeOfDay::reported(Some(0), Some(35.23), Some(-80.84), noon),
+            TimeOfDay::Night
+        );
+        assert_eq!(
+            TimeOfDay::reported(None, Some(35.23), Some(-80.84), noon),
+            TimeOfDay::Day
+        );
+        assert_eq!(
+            TimeOfDay::reported(None, None, None, 1_718_949_600),
+            TimeOfDay::Day
+        );
+    }
+
     #[test]
     fn valid_code_for_day() {
         let icon =
@@ -4,19 +4,29 @@ use std::fmt;
 
 use serde::{Deserialize, Deserializer, Serialize};
 
+pub mod air_quality;
+pub mod alerts;
 pub(crate) mod api;
+pub mod astro;
 pub mod cache;
 pub mod conditions;
 pub mod config;
+pub mod error;
+pub mod history;
 pub mod icons;
 pub mod location;
+pub mod rules;
+pub mod units;
+pub mod waybar;
 mod weather;
+pub mod webhook;
 
 pub use cache::Cache;
 pub use conditions::Conditions;
 pub use config::Config;
+pub use error::{Error, Result};
 
-#[derive(Clone, Copy, Debug, Default, Serialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
 pub enum Unit {
     C,
     #[default]
@@ -53,7 +63,7 @@ impl fmt::Display for Unit {
 }
 
 impl<'de> Deserialize<'de> for Unit {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
@@ -0,0 +1,123 @@
+use super::Location;
+use crate::{Error, Result};
+
+const GEO_SCHEME: &str = "geo:";
+
+impl Location {
+    /// Builds a location directly from coordinates, no geocoding involved.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidLocation` if `latitude` is outside -90..=90 or
+    /// `longitude` is outside -180..=180.
+    pub fn from_coordinates(latitude: f64, longitude: f64) -> Result<Self> {
+        if !(-90.0..=90.0).contains(&latitude) {
+            return Err(Error::InvalidLocation(format!(
+                "latitude {latitude} is out of range, expect -90 to 90"
+            )));
+        }
+
+        if !(-180.0..=180.0).contains(&longitude) {
+            return Err(Error::InvalidLocation(format!(
+                "longitude {longitude} is out of range, expect -180 to 180"
+            )));
+        }
+
+        let latitude = latitude.to_string();
+        let longitude = longitude.to_string();
+
+        Ok(Self {
+            loc: format!("{latitude},{longitude}"),
+            latitude,
+            longitude,
+            ..Default::default()
+        })
+    }
+}
+
+/// Returns whether `region` is a `geo:` URI (RFC 5870).
+pub(crate) fn is_geo_uri(region: &str) -> bool {
+    region
+        .get(..GEO_SCHEME.len())
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case(GEO_SCHEME))
+}
+
+/// Parses a `geo:` URI such as `geo:35.15,-80.81`. An altitude and any
+/// parameters (`;u=10`) are accepted and ignored.
+///
+/// # Errors
+///
+/// Returns `Error::InvalidLocation` if the URI is malformed or the coordinates
+/// are out of range.
+pub(crate) fn parse(region: &str) -> Result<Location> {
+    let invalid = || {
+        Error::InvalidLocation(format!(
+            "invalid geo URI \"{region}\", expect geo:LATITUDE,LONGITUDE"
+        ))
+    };
+
+    let coordinates = region[GEO_SCHEME.len()..]
+        .split(';')
+        .next()
+        .unwrap_or_default();
+    let parts: Vec<&str> = coordinates.split(',').map(str::trim).collect();
+
+    let [latitude, longitude, ..] = parts[..] else {
+        return Err(invalid());
+    };
+
+    if parts.len() > 3 {
+        return Err(invalid());
+    }
+
+    let latitude = latitude.parse::<f64>().map_err(|_| invalid())?;
+    let longitude = longitude.parse::<f64>().map_err(|_| invalid())?;
+
+    Location::from_coordinates(latitude, longitude)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_builds_location_from_coordinates() {
+        let location = Location::from_coordinates(35.15, -80.81).unwrap();
+
+        assert_eq!(location.loc, "35.15,-80.81");
+        assert_eq!(location.latitude, "35.15");
+        assert_eq!(location.longitude, "-80.81");
+        assert_eq!(location.postal_code, "");
+    }
+
+    #[test]
+    fn it_rejects_out_of_range_coordinates() {
+        assert!(Location::from_coordinates(90.5, 0.0).is_err());
+        assert!(Location::from_coordinates(0.0, -180.5).is_err());
+        assert!(Location::from_coordinates(f64::NAN, 0.0).is_err());
+    }
+
+    #[test]
+    fn it_detects_geo_uris() {
+        assert!(is_geo_uri("geo:35.15,-80.81"));
+        assert!(is_geo_uri("GEO:35.15,-80.81"));
+        assert!(!is_geo_uri("10001,US"));
+        assert!(!is_geo_uri("geo"));
+    }
+
+    #[test]
+    fn it_parses_geo_uris() {
+        let location = parse("geo:35.15,-80.81").unwrap();
+
+        assert_eq!(location.loc, "35.15,-80.81");
+        assert_eq!(parse("geo:35.15,-80.81,220;u=10").unwrap(), location);
+    }
+
+    #[test]
+    fn it_rejects_invalid_geo_uris() {
+        assert!(parse("geo:35.15").is_err());
+        assert!(parse("geo:north,west").is_err());
+        assert!(parse("geo:1,2,3,4").is_err());
+        assert!(parse("geo:95,0").is_err());
+    }
+}
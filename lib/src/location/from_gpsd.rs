@@ -0,0 +1,196 @@
+use std::{
+    io::{BufRead, BufReader, ErrorKind, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+
+use super::Location;
+use crate::{config::Config, Error, Result};
+
+const PROVIDER: &str = "gpsd";
+
+// gpsd reports one JSON object per line, once watching is enabled a `TPV`
+// (time-position-velocity) report is sent for every fix:
+//
+// {"class":"VERSION","release":"3.25","rev":"3.25","proto_major":3,"proto_minor":15}
+// {"class":"DEVICES","devices":[{"class":"DEVICE","path":"/dev/ttyACM0"}]}
+// {"class":"TPV","device":"/dev/ttyACM0","mode":3,"lat":35.1287,"lon":-80.9338,"alt":221.4}
+//
+// `mode` is 0 (unknown) or 1 (no fix) until the receiver has a 2D or 3D fix.
+
+#[derive(Debug, Deserialize)]
+struct Report {
+    class: String,
+    #[serde(default)]
+    mode: u8,
+    lat: Option<f64>,
+    lon: Option<f64>,
+}
+
+/// Reads the first 2D or 3D fix reported by the gpsd daemon listening on
+/// `config.gpsd_address`.
+///
+/// The connect and read timeouts of the `gpsd` network policy apply, the
+/// read timeout also bounds the total time spent waiting for a fix.
+///
+/// # Errors
+///
+/// - `Error::Network` when gpsd can't be reached.
+/// - `Error::LocationNotFound` when no fix was reported in time.
+pub(crate) fn locate(config: &Config) -> Result<Location> {
+    let policy = config.network.policy(PROVIDER);
+    let timeout = Duration::from_millis(policy.read_timeout_ms);
+    let unreachable = |err: std::io::Error| {
+        log::debug!("gpsd at {} unreachable: {err}", config.gpsd_address);
+
+        Error::Network {
+            provider: PROVIDER.to_string(),
+            status: None,
+        }
+    };
+
+    let address = config
+        .gpsd_address
+        .to_socket_addrs()
+        .map_err(unreachable)?
+        .next()
+        .ok_or_else(|| unreachable(ErrorKind::NotFound.into()))?;
+    let mut stream = TcpStream::connect_timeout(
+        &address,
+        Duration::from_millis(policy.connect_timeout_ms),
+    )
+    .map_err(unreachable)?;
+
+    stream
+        .set_read_timeout(Some(timeout))
+        .map_err(unreachable)?;
+    stream
+        .write_all(b"?WATCH={\"enable\":true,\"json\":true};\n")
+        .map_err(unreachable)?;
+
+    let deadline = Instant::now() + timeout;
+
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => {
+                log::debug!("reading from gpsd failed: {err}");
+
+                break;
+            }
+        };
+
+        if let Some((lat, lon)) = parse_fix(&line) {
+            return Location::from_coordinates(lat, lon);
+        }
+
+        if Instant::now() > deadline {
+            break;
+        }
+    }
+
+    Err(Error::LocationNotFound("no fix from gpsd".to_string()))
+}
+
+/// Extracts the coordinates from a `TPV` report holding a 2D or 3D fix,
+/// other reports (and anything that isn't one) are ignored.
+fn parse_fix(line: &str) -> Option<(f64, f64)> {
+    let report: Report = serde_json::from_str(line).ok()?;
+
+    if report.class != "TPV" || report.mode < 2 {
+        return None;
+    }
+
+    report.lat.zip(report.lon)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, thread};
+
+    use super::*;
+
+    /// Stands in for gpsd, writing `lines` once the watch command was read.
+    fn serve(lines: &'static [&'static str]) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut command = String::new();
+
+            reader.read_line(&mut command).unwrap();
+            assert!(command.starts_with("?WATCH="));
+
+            for line in lines {
+                writeln!(stream, "{line}").unwrap();
+            }
+        });
+
+        address
+    }
+
+    fn config(gpsd_address: String) -> Config {
+        Config {
+            gpsd_address,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_parses_fix() {
+        assert_eq!(
+            parse_fix(r#"{"class":"TPV","mode":3,"lat":35.1,"lon":-80.9}"#),
+            Some((35.1, -80.9))
+        );
+        assert_eq!(parse_fix(r#"{"class":"TPV","mode":1}"#), None);
+        assert_eq!(
+            parse_fix(r#"{"class":"SKY","mode":3,"lat":35.1,"lon":-80.9}"#),
+            None
+        );
+        assert_eq!(parse_fix("not json"), None);
+    }
+
+    #[test]
+    fn it_waits_for_fix() {
+        let address = serve(&[
+            r#"{"class":"VERSION","release":"3.25"}"#,
+            r#"{"class":"DEVICES","devices":[]}"#,
+            r#"{"class":"TPV","mode":1}"#,
+            r#"{"class":"TPV","mode":3,"lat":35.1287,"lon":-80.9338}"#,
+        ]);
+
+        let location = locate(&config(address)).unwrap();
+
+        assert_eq!(location.loc, "35.1287,-80.9338");
+    }
+
+    #[test]
+    fn it_fails_without_fix() {
+        let address = serve(&[
+            r#"{"class":"VERSION","release":"3.25"}"#,
+            r#"{"class":"TPV","mode":1}"#,
+        ]);
+
+        assert!(matches!(
+            locate(&config(address)),
+            Err(Error::LocationNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn it_fails_when_unreachable() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        drop(listener);
+
+        assert!(matches!(
+            locate(&config(address)),
+            Err(Error::Network { .. })
+        ));
+    }
+}
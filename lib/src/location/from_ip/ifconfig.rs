@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+
+use super::{location, parse_error};
+use crate::{
+    config::{Config, RequestPolicy},
+    location::Location,
+    Error,
+};
+
+const PROVIDER: &str = "ifconfig";
+
+// https://ifconfig.co/json
+// {
+//   "ip": "75.189.252.56",
+//   "country": "United States",
+//   "country_iso": "US",
+//   "region_name": "North Carolina",
+//   "zip_code": "28273",
+//   "city": "Charlotte",
+//   "latitude": 35.1287,
+//   "longitude": -80.9338,
+//   "time_zone": "America/New_York"
+// }
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Response {
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    zip_code: Option<String>,
+    city: Option<String>,
+    region_name: Option<String>,
+    country_iso: Option<String>,
+}
+
+impl TryFrom<Response> for Location {
+    type Error = Error;
+
+    fn try_from(response: Response) -> Result<Self, Self::Error> {
+        let (Some(lat), Some(lon)) = (response.latitude, response.longitude)
+        else {
+            return Err(parse_error(PROVIDER, &"missing coordinates"));
+        };
+
+        location(
+            PROVIDER,
+            lat,
+            lon,
+            response.zip_code,
+            &[response.city, response.region_name, response.country_iso],
+        )
+    }
+}
+
+pub struct Client {
+    policy: RequestPolicy,
+}
+
+impl Client {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            policy: config.network.policy(PROVIDER),
+        }
+    }
+}
+
+impl crate::api::Fetchable<Response, Location> for Client {
+    fn provider(&self) -> &'static str {
+        PROVIDER
+    }
+
+    fn url(&self) -> &'static str {
+        "https://ifconfig.co/json"
+    }
+
+    fn policy(&self) -> RequestPolicy {
+        self.policy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_converts_response() {
+        let response: Response = serde_json::from_str(
+            r#"{
+                "ip": "75.189.252.56",
+                "country_iso": "US",
+                "region_name": "North Carolina",
+                "zip_code": "28273",
+                "city": "Charlotte",
+                "latitude": 35.1287,
+                "longitude": -80.9338
+            }"#,
+        )
+        .unwrap();
+
+        let location = Location::try_from(response).unwrap();
+
+        assert_eq!(location.loc, "35.1287,-80.9338");
+        assert_eq!(location.postal_code, "28273");
+        assert_eq!(
+            location.name,
+            Some("Charlotte, North Carolina, US".to_string())
+        );
+    }
+
+    #[test]
+    fn it_rejects_missing_coordinates() {
+        let response: Response =
+            serde_json::from_str(r#"{"ip": "75.189.252.56"}"#).unwrap();
+
+        assert!(matches!(
+            Location::try_from(response),
+            Err(Error::Parse { .. })
+        ));
+    }
+}
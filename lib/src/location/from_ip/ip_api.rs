@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+
+use super::{location, parse_error};
+use crate::{
+    config::{Config, RequestPolicy},
+    location::Location,
+    Error,
+};
+
+const PROVIDER: &str = "ip_api";
+
+// http://ip-api.com/json
+// {
+//   "status": "success",
+//   "country": "United States",
+//   "countryCode": "US",
+//   "region": "NC",
+//   "regionName": "North Carolina",
+//   "city": "Charlotte",
+//   "zip": "28273",
+//   "lat": 35.1287,
+//   "lon": -80.9338,
+//   "timezone": "America/New_York",
+//   "query": "75.189.252.56"
+// }
+//
+// { "status": "fail", "message": "private range", "query": "10.0.0.1" }
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Response {
+    status: String,
+    message: Option<String>,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    zip: Option<String>,
+    city: Option<String>,
+    region_name: Option<String>,
+    country_code: Option<String>,
+}
+
+impl TryFrom<Response> for Location {
+    type Error = Error;
+
+    fn try_from(response: Response) -> Result<Self, Self::Error> {
+        if response.status != "success" {
+            return Err(parse_error(
+                PROVIDER,
+                &response.message.unwrap_or(response.status),
+            ));
+        }
+
+        let (Some(lat), Some(lon)) = (response.lat, response.lon) else {
+            return Err(parse_error(PROVIDER, &"missing coordinates"));
+        };
+
+        location(
+            PROVIDER,
+            lat,
+            lon,
+            response.zip,
+            &[response.city, response.region_name, response.country_code],
+        )
+    }
+}
+
+pub struct Client {
+    policy: RequestPolicy,
+}
+
+impl Client {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            policy: config.network.policy(PROVIDER),
+        }
+    }
+}
+
+impl crate::api::Fetchable<Response, Location> for Client {
+    fn provider(&self) -> &'static str {
+        PROVIDER
+    }
+
+    // the free tier is only available over http
+    fn url(&self) -> &'static str {
+        "http://ip-api.com/json"
+    }
+
+    fn policy(&self) -> RequestPolicy {
+        self.policy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_converts_successful_response() {
+        let response: Response = serde_json::from_str(
+            r#"{
+                "status": "success",
+                "countryCode": "US",
+                "regionName": "North Carolina",
+                "city": "Charlotte",
+                "zip": "28273",
+                "lat": 35.1287,
+                "lon": -80.9338
+            }"#,
+        )
+        .unwrap();
+
+        let location = Location::try_from(response).unwrap();
+
+        assert_eq!(location.loc, "35.1287,-80.9338");
+        assert_eq!(location.postal_code, "28273");
+        assert_eq!(
+            location.name,
+            Some("Charlotte, North Carolina, US".to_string())
+        );
+    }
+
+    #[test]
+    fn it_rejects_failed_response() {
+        let response: Response = serde_json::from_str(
+            r#"{"status": "fail", "message": "private range"}"#,
+        )
+        .unwrap();
+
+        match Location::try_from(response) {
+            Err(Error::Parse { message, .. }) => {
+                assert_eq!(message, "private range");
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+}
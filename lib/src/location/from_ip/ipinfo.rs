@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+use super::{location, parse_error};
+use crate::{
+    config::{Config, RequestPolicy},
+    location::Location,
+    Error,
+};
+
+const PROVIDER: &str = "ipinfo";
+
+// {
+//   "ip": "75.189.252.56",
+//   "hostname": "cpe-75-189-252-56.nc.res.rr.com",
+//   "city": "Charlotte",
+//   "region": "North Carolina",
+//   "country": "US",
+//   "loc": "35.1287,-80.9338",
+//   "org": "AS11426 Charter Communications Inc",
+//   "postal": "28273",
+//   "timezone": "America/New_York",
+//   "readme": "https://ipinfo.io/missingauth"
+// }
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Response {
+    loc: String,
+    #[serde(default)]
+    postal: Option<String>,
+    #[serde(default)]
+    city: Option<String>,
+    #[serde(default)]
+    region: Option<String>,
+    #[serde(default)]
+    country: Option<String>,
+}
+
+impl TryFrom<Response> for Location {
+    type Error = Error;
+
+    fn try_from(response: Response) -> Result<Self, Self::Error> {
+        let invalid = || {
+            parse_error(PROVIDER, &format!("invalid loc \"{}\"", response.loc))
+        };
+        let (lat, lon) = response.loc.split_once(',').ok_or_else(invalid)?;
+        let lat = lat.trim().parse::<f64>().map_err(|_| invalid())?;
+        let lon = lon.trim().parse::<f64>().map_err(|_| invalid())?;
+
+        location(
+            PROVIDER,
+            lat,
+            lon,
+            response.postal,
+            &[response.city, response.region, response.country],
+        )
+    }
+}
+
+pub struct Client {
+    policy: RequestPolicy,
+}
+
+impl Client {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            policy: config.network.policy(PROVIDER),
+        }
+    }
+}
+
+impl crate::api::Fetchable<Response, Location> for Client {
+    fn provider(&self) -> &'static str {
+        PROVIDER
+    }
+
+    fn url(&self) -> &'static str {
+        "https://ipinfo.io/json"
+    }
+
+    fn policy(&self) -> RequestPolicy {
+        self.policy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_location_from() {
+        let lat = "35.12345";
+        let long = "-80.54321";
+        let postal_code = "10001";
+        let loc = format!("{lat},{long}");
+
+        let response = Response {
+            loc: loc.clone(),
+            postal: Some(postal_code.to_string()),
+            city: Some("Charlotte".to_string()),
+            region: Some("North Carolina".to_string()),
+            country: Some("US".to_string()),
+        };
+
+        let location = Location::try_from(response).unwrap();
+
+        assert_eq!(location.loc, loc);
+        assert_eq!(location.latitude, lat);
+        assert_eq!(location.longitude, long);
+        assert_eq!(location.postal_code, postal_code);
+        assert_eq!(
+            location.name,
+            Some("Charlotte, North Carolina, US".to_string())
+        );
+    }
+
+    #[test]
+    fn it_rejects_malformed_loc() {
+        for loc in ["", "35.1", "north,west"] {
+            let response = Response {
+                loc: loc.to_string(),
+                ..Default::default()
+            };
+
+            assert!(matches!(
+                Location::try_from(response),
+                Err(Error::Parse { .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+
+use super::{location, parse_error};
+use crate::{
+    config::{Config, RequestPolicy},
+    location::Location,
+    Error,
+};
+
+const PROVIDER: &str = "ipwho";
+
+// https://ipwho.is/
+// {
+//   "ip": "75.189.252.56",
+//   "success": true,
+//   "country": "United States",
+//   "country_code": "US",
+//   "region": "North Carolina",
+//   "city": "Charlotte",
+//   "latitude": 35.1287,
+//   "longitude": -80.9338,
+//   "postal": "28273"
+// }
+//
+// { "ip": "10.0.0.1", "success": false, "message": "Reserved range" }
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Response {
+    success: bool,
+    message: Option<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    postal: Option<String>,
+    city: Option<String>,
+    region: Option<String>,
+    country_code: Option<String>,
+}
+
+impl TryFrom<Response> for Location {
+    type Error = Error;
+
+    fn try_from(response: Response) -> Result<Self, Self::Error> {
+        if !response.success {
+            return Err(parse_error(
+                PROVIDER,
+                &response.message.unwrap_or("lookup failed".to_string()),
+            ));
+        }
+
+        let (Some(lat), Some(lon)) = (response.latitude, response.longitude)
+        else {
+            return Err(parse_error(PROVIDER, &"missing coordinates"));
+        };
+
+        location(
+            PROVIDER,
+            lat,
+            lon,
+            response.postal,
+            &[response.city, response.region, response.country_code],
+        )
+    }
+}
+
+pub struct Client {
+    policy: RequestPolicy,
+}
+
+impl Client {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            policy: config.network.policy(PROVIDER),
+        }
+    }
+}
+
+impl crate::api::Fetchable<Response, Location> for Client {
+    fn provider(&self) -> &'static str {
+        PROVIDER
+    }
+
+    fn url(&self) -> &'static str {
+        "https://ipwho.is/"
+    }
+
+    fn policy(&self) -> RequestPolicy {
+        self.policy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_converts_successful_response() {
+        let response: Response = serde_json::from_str(
+            r#"{
+                "success": true,
+                "country_code": "US",
+                "region": "North Carolina",
+                "city": "Charlotte",
+                "latitude": 35.1287,
+                "longitude": -80.9338,
+                "postal": "28273"
+            }"#,
+        )
+        .unwrap();
+
+        let location = Location::try_from(response).unwrap();
+
+        assert_eq!(location.loc, "35.1287,-80.9338");
+        assert_eq!(location.postal_code, "28273");
+        assert_eq!(
+            location.name,
+            Some("Charlotte, North Carolina, US".to_string())
+        );
+    }
+
+    #[test]
+    fn it_rejects_failed_response() {
+        let response: Response = serde_json::from_str(
+            r#"{"success": false, "message": "Reserved range"}"#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            Location::try_from(response),
+            Err(Error::Parse { .. })
+        ));
+    }
+}
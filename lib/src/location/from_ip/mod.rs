@@ -0,0 +1,236 @@
+use std::{
+    fs,
+    net::{Ipv4Addr, UdpSocket},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::Location;
+use crate::{api::Fetchable, config::Config, Error, Result};
+
+pub(crate) mod ifconfig;
+pub(crate) mod ip_api;
+pub(crate) mod ipinfo;
+pub(crate) mod ipwho;
+
+/// Services able to geolocate the machine from its public IP address.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IpProvider {
+    /// <https://ipinfo.io>
+    Ipinfo,
+    /// <https://ip-api.com>
+    IpApi,
+    /// <https://ipwho.is>
+    Ipwho,
+    /// <https://ifconfig.co>
+    Ifconfig,
+}
+
+impl IpProvider {
+    /// Every provider, in the default fallback order.
+    #[must_use]
+    pub fn all() -> Vec<Self> {
+        vec![
+            IpProvider::Ipinfo,
+            IpProvider::IpApi,
+            IpProvider::Ipwho,
+            IpProvider::Ifconfig,
+        ]
+    }
+}
+
+/// Geolocates the machine from its public IP address, trying each of
+/// `config.ip_providers` in order until one succeeds.
+///
+/// # Errors
+///
+/// Returns `Error::LocationUnavailable`, holding the error from each provider,
+/// when none of them succeeds.
+pub(crate) fn locate(config: &Config) -> Result<Location> {
+    let mut errors = vec![];
+
+    for provider in &config.ip_providers {
+        let result = match provider {
+            IpProvider::Ipinfo => ipinfo::Client::new(config).fetch(),
+            IpProvider::IpApi => ip_api::Client::new(config).fetch(),
+            IpProvider::Ipwho => ipwho::Client::new(config).fetch(),
+            IpProvider::Ifconfig => ifconfig::Client::new(config).fetch(),
+        };
+
+        match result {
+            Ok(location) => return Ok(location),
+            Err(err) => {
+                log::debug!("ip geolocation failed: {err}");
+
+                errors.push(err);
+            }
+        }
+    }
+
+    Err(Error::LocationUnavailable(errors))
+}
+
+/// Identifies the network the machine is on by the local address of the
+/// interface that would route to the internet, so that a cached IP-derived
+/// location is dropped when moving between networks.
+///
+/// Private addresses such as `192.168.1.10` are handed out on many networks,
+/// so the hardware address of the default gateway is appended when it is
+/// known (Linux only), eg. `192.168.1.10@a4:91:b1:0c:22:7e`.
+///
+/// Connecting a UDP socket only picks the route, no packets are sent. Falls
+/// back to `unknown` when there is no route (eg. offline).
+pub(crate) fn network() -> String {
+    let address = UdpSocket::bind("0.0.0.0:0")
+        .and_then(|socket| {
+            socket.connect("1.1.1.1:53")?;
+            socket.local_addr()
+        })
+        .map_or_else(|_| "unknown".to_string(), |addr| addr.ip().to_string());
+
+    match gateway() {
+        Some(gateway) => format!("{address}@{gateway}"),
+        None => address,
+    }
+}
+
+/// Hardware address of the default gateway, from the kernel's routing and
+/// ARP tables.
+fn gateway() -> Option<String> {
+    let routes = fs::read_to_string("/proc/net/route").ok()?;
+    let arp = fs::read_to_string("/proc/net/arp").ok()?;
+
+    gateway_hardware_address(&routes, &arp)
+}
+
+fn gateway_hardware_address(routes: &str, arp: &str) -> Option<String> {
+    // Iface Destination Gateway Flags ..., the default route goes to 0.0.0.0
+    let gateway = routes.lines().skip(1).find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+
+        match fields.as_slice() {
+            [_, "00000000", gateway, ..] if *gateway != "00000000" => {
+                Some(*gateway)
+            }
+            _ => None,
+        }
+    })?;
+    // addresses are in network byte order, printed as a native integer
+    let gateway =
+        Ipv4Addr::from(u32::from_str_radix(gateway, 16).ok()?.to_ne_bytes())
+            .to_string();
+
+    // IP-address HW-type Flags HW-address Mask Device
+    arp.lines().skip(1).find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+
+        match fields.as_slice() {
+            [ip, _, _, hardware, ..]
+                if *ip == gateway && *hardware != "00:00:00:00:00:00" =>
+            {
+                Some((*hardware).to_string())
+            }
+            _ => None,
+        }
+    })
+}
+
+/// Builds the `Location` shared by every provider's response, mapping
+/// coordinates that are out of range to a parse error for `provider`.
+fn location(
+    provider: &str,
+    latitude: f64,
+    longitude: f64,
+    postal_code: Option<String>,
+    name: &[Option<String>],
+) -> Result<Location> {
+    let mut location = Location::from_coordinates(latitude, longitude)
+        .map_err(|err| parse_error(provider, &err))?;
+    let name = name
+        .iter()
+        .flatten()
+        .filter(|part| !part.is_empty())
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    location.postal_code = postal_code.unwrap_or_default();
+    location.name = Some(name).filter(|name| !name.is_empty());
+
+    Ok(location)
+}
+
+fn parse_error(provider: &str, message: &impl ToString) -> Error {
+    Error::Parse {
+        provider: provider.to_string(),
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_builds_location() {
+        let location = location(
+            "test",
+            35.1287,
+            -80.9338,
+            Some("28273".to_string()),
+            &[
+                Some("Charlotte".to_string()),
+                Some(String::new()),
+                None,
+                Some("US".to_string()),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(location.loc, "35.1287,-80.9338");
+        assert_eq!(location.postal_code, "28273");
+        assert_eq!(location.name, Some("Charlotte, US".to_string()));
+    }
+
+    #[test]
+    fn it_finds_gateway_hardware_address() {
+        let gateway = u32::from_ne_bytes([192, 168, 1, 1]);
+        let routes = format!(
+            "Iface\tDestination\tGateway \tFlags\n\
+             wlan0\t00000000\t{gateway:08X}\t0003\n\
+             wlan0\t0001A8C0\t00000000\t0001\n"
+        );
+        let arp = "IP address       HW type     Flags       HW address            Mask     Device
+192.168.1.7      0x1         0x2         11:22:33:44:55:66     *        wlan0
+192.168.1.1      0x1         0x2         a4:91:b1:0c:22:7e     *        wlan0
+";
+
+        assert_eq!(
+            gateway_hardware_address(&routes, arp),
+            Some("a4:91:b1:0c:22:7e".to_string())
+        );
+        assert_eq!(gateway_hardware_address("Iface\n", arp), None);
+    }
+
+    #[test]
+    fn it_rejects_invalid_coordinates() {
+        assert!(matches!(
+            location("test", 135.0, 0.0, None, &[]),
+            Err(Error::Parse { .. })
+        ));
+    }
+
+    #[test]
+    fn it_fails_without_providers() {
+        let config = Config {
+            ip_providers: vec![],
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            locate(&config),
+            Err(Error::LocationUnavailable(errors)) if errors.is_empty()
+        ));
+    }
+}
@@ -0,0 +1,512 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::Location;
+use crate::{
+    api::Fetchable,
+    config::{Config, RequestPolicy},
+    Error, Result,
+};
+
+const PROVIDER: &str = "open_meteo_geocoding";
+
+// https://geocoding-api.open-meteo.com/v1/search?name=Lisbon&countryCode=PT
+// {
+//   "results": [
+//     {
+//       "id": 2267057,
+//       "name": "Lisbon",
+//       "latitude": 38.71667,
+//       "longitude": -9.13333,
+//       "elevation": 45,
+//       "feature_code": "PPLC",
+//       "country_code": "PT",
+//       "admin1": "Lisbon",
+//       "timezone": "Europe/Lisbon",
+//       "population": 517802,
+//       "country": "Portugal",
+//       "postcodes": ["1100-148"]
+//     }
+//   ],
+//   "generationtime_ms": 0.5
+// }
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Place {
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    country_code: Option<String>,
+    country: Option<String>,
+    admin1: Option<String>,
+    postcodes: Option<Vec<String>>,
+}
+
+impl fmt::Display for Place {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let parts: Vec<&str> = [
+            Some(self.name.as_str()),
+            self.admin1.as_deref(),
+            self.country.as_deref().or(self.country_code.as_deref()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        write!(fmt, "{}", parts.join(", "))
+    }
+}
+
+impl From<Place> for Location {
+    fn from(place: Place) -> Self {
+        let latitude = place.latitude.to_string();
+        let longitude = place.longitude.to_string();
+        let name = place.to_string();
+
+        Self {
+            loc: format!("{latitude},{longitude}"),
+            latitude,
+            longitude,
+            postal_code: place
+                .postcodes
+                .and_then(|codes| codes.into_iter().next())
+                .unwrap_or_default(),
+            name: Some(name),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Response {
+    #[serde(default)]
+    results: Vec<Place>,
+}
+
+impl From<Response> for Vec<Place> {
+    fn from(response: Response) -> Self {
+        response.results
+    }
+}
+
+/// Abbreviations of the regions commonly written as `<place>, <code>`, which
+/// the geocoding API only reports by name. A code can stand for regions of
+/// different countries, eg. `WA` for Washington and Western Australia.
+const REGION_CODES: &[(&str, &[&str])] = &[
+    ("AL", &["Alabama"]),
+    ("AK", &["Alaska"]),
+    ("AZ", &["Arizona"]),
+    ("AR", &["Arkansas"]),
+    ("CA", &["California"]),
+    ("CO", &["Colorado"]),
+    ("CT", &["Connecticut"]),
+    ("DE", &["Delaware"]),
+    ("DC", &["District of Columbia"]),
+    ("FL", &["Florida"]),
+    ("GA", &["Georgia"]),
+    ("HI", &["Hawaii"]),
+    ("ID", &["Idaho"]),
+    ("IL", &["Illinois"]),
+    ("IN", &["Indiana"]),
+    ("IA", &["Iowa"]),
+    ("KS", &["Kansas"]),
+    ("KY", &["Kentucky"]),
+    ("LA", &["Louisiana"]),
+    ("ME", &["Maine"]),
+    ("MD", &["Maryland"]),
+    ("MA", &["Massachusetts"]),
+    ("MI", &["Michigan"]),
+    ("MN", &["Minnesota"]),
+    ("MS", &["Mississippi"]),
+    ("MO", &["Missouri"]),
+    ("MT", &["Montana"]),
+    ("NE", &["Nebraska"]),
+    ("NV", &["Nevada"]),
+    ("NH", &["New Hampshire"]),
+    ("NJ", &["New Jersey"]),
+    ("NM", &["New Mexico"]),
+    ("NY", &["New York"]),
+    ("NC", &["North Carolina"]),
+    ("ND", &["North Dakota"]),
+    ("OH", &["Ohio"]),
+    ("OK", &["Oklahoma"]),
+    ("OR", &["Oregon"]),
+    ("PA", &["Pennsylvania"]),
+    ("RI", &["Rhode Island"]),
+    ("SC", &["South Carolina"]),
+    ("SD", &["South Dakota"]),
+    ("TN", &["Tennessee"]),
+    ("TX", &["Texas"]),
+    ("UT", &["Utah"]),
+    ("VT", &["Vermont"]),
+    ("VA", &["Virginia"]),
+    ("WA", &["Washington", "Western Australia"]),
+    ("WV", &["West Virginia"]),
+    ("WI", &["Wisconsin"]),
+    ("WY", &["Wyoming"]),
+    ("AB", &["Alberta"]),
+    ("BC", &["British Columbia"]),
+    ("MB", &["Manitoba"]),
+    ("NB", &["New Brunswick"]),
+    ("NL", &["Newfoundland and Labrador"]),
+    ("NS", &["Nova Scotia"]),
+    ("NT", &["Northwest Territories", "Northern Territory"]),
+    ("NU", &["Nunavut"]),
+    ("ON", &["Ontario"]),
+    ("PE", &["Prince Edward Island"]),
+    ("QC", &["Quebec"]),
+    ("SK", &["Saskatchewan"]),
+    ("YT", &["Yukon"]),
+    ("SA", &["South Australia"]),
+];
+
+/// Searches for places by name, eg. `Lisbon` or `Lisbon, PT`.
+///
+/// Anything after the first comma narrows the search, a two letter value is
+/// treated as a country code and, when it is also a region code, as a region
+/// code too (`Paris, CA` matches Paris, Ontario, Canada as well as Paris,
+/// California). Anything else must match the region (state, province, etc.)
+/// or country name of the place.
+#[derive(Debug)]
+pub struct Client {
+    region: String,
+    qualifier: Option<String>,
+    policy: RequestPolicy,
+    query: Vec<(String, String)>,
+}
+
+impl Client {
+    pub fn new(config: &Config, region: &str) -> Result<Self> {
+        let (name, qualifier) = match region.split_once(',') {
+            Some((name, qualifier)) => (name.trim(), Some(qualifier.trim())),
+            None => (region.trim(), None),
+        };
+
+        if name.is_empty() {
+            return Err(Error::InvalidLocation(
+                "missing place name".to_string(),
+            ));
+        }
+
+        let mut query = vec![
+            ("name".to_string(), name.to_string()),
+            ("count".to_string(), "10".to_string()),
+            ("format".to_string(), "json".to_string()),
+        ];
+        let mut qualifier = qualifier.filter(|q| !q.is_empty());
+
+        if let Some(code) = qualifier.filter(|q| is_country_code(q)) {
+            query.push(("countryCode".to_string(), code.to_uppercase()));
+            qualifier = None;
+        }
+
+        Ok(Self {
+            region: region.to_string(),
+            qualifier: qualifier.map(str::to_lowercase),
+            policy: config.network.policy(PROVIDER),
+            query,
+        })
+    }
+
+    /// Searches for the place, returning its location.
+    ///
+    /// # Arguments
+    ///
+    /// * `pick` - 1-based index of the match to use when the search is
+    ///   ambiguous.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::LocationNotFound` - If nothing matches.
+    /// * `Error::AmbiguousLocation` - If several places match and `pick` is
+    ///   `None`.
+    /// * `Error::InvalidLocation` - If `pick` is out of range.
+    pub fn locate(&self, pick: Option<usize>) -> Result<Location> {
+        let mut places = self.matches(self.fetch()?);
+
+        if let Some(client) = self.by_region_code() {
+            places = merge(places, client.matches(client.fetch()?));
+        }
+
+        select(&self.region, places, pick)
+    }
+
+    /// The same search with the country code used as a region code instead,
+    /// `None` when the query has no country code or it isn't a region code.
+    fn by_region_code(&self) -> Option<Self> {
+        let (_, code) = self
+            .query
+            .iter()
+            .find(|(key, _)| key == "countryCode")
+            .filter(|(_, code)| {
+                REGION_CODES.iter().any(|(region, _)| region == code)
+            })?;
+
+        Some(Self {
+            region: self.region.clone(),
+            qualifier: Some(code.to_lowercase()),
+            policy: self.policy,
+            query: self
+                .query
+                .iter()
+                .filter(|(key, _)| key != "countryCode")
+                .cloned()
+                .collect(),
+        })
+    }
+
+    fn matches(&self, places: Vec<Place>) -> Vec<Place> {
+        let Some(qualifier) = &self.qualifier else {
+            return places;
+        };
+
+        places
+            .into_iter()
+            .filter(|place| {
+                let region_codes =
+                    place.admin1.as_deref().into_iter().flat_map(|admin1| {
+                        REGION_CODES
+                            .iter()
+                            .filter(move |(_, names)| names.contains(&admin1))
+                            .map(|(code, _)| *code)
+                    });
+
+                [place.admin1.as_deref(), place.country.as_deref()]
+                    .into_iter()
+                    .flatten()
+                    .chain(region_codes)
+                    .any(|name| name.to_lowercase() == *qualifier)
+            })
+            .collect()
+    }
+}
+
+impl Fetchable<Response, Vec<Place>> for Client {
+    fn provider(&self) -> &'static str {
+        PROVIDER
+    }
+
+    fn url(&self) -> &'static str {
+        "https://geocoding-api.open-meteo.com/v1/search"
+    }
+
+    fn policy(&self) -> RequestPolicy {
+        self.policy
+    }
+
+    fn query(&self) -> Option<&Vec<(String, String)>> {
+        Some(&self.query)
+    }
+}
+
+/// Heuristic used to tell postal codes from place names: postal codes contain
+/// at least one digit (`10001`, `SW1A 1AA`) while place names do not.
+pub(crate) fn is_place_name(region: &str) -> bool {
+    let name = region.split_once(',').map_or(region, |(name, _)| name);
+
+    !name.chars().any(|c| c.is_ascii_digit())
+}
+
+fn is_country_code(value: &str) -> bool {
+    value.len() == 2 && value.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+/// Appends the places in `more` that aren't in `places` already.
+fn merge(mut places: Vec<Place>, more: Vec<Place>) -> Vec<Place> {
+    for place in more {
+        if !places.contains(&place) {
+            places.push(place);
+        }
+    }
+
+    places
+}
+
+fn select(
+    region: &str,
+    mut places: Vec<Place>,
+    pick: Option<usize>,
+) -> Result<Location> {
+    match (places.len(), pick) {
+        (0, _) => Err(Error::LocationNotFound(region.to_string())),
+        (1, None) => Ok(places.remove(0).into()),
+        (_, None) => Err(Error::AmbiguousLocation {
+            query: region.to_string(),
+            candidates: places.iter().map(ToString::to_string).collect(),
+        }),
+        (count, Some(pick)) if pick == 0 || pick > count => {
+            Err(Error::InvalidLocation(format!(
+                "cannot pick {pick}, \"{region}\" has {count} matches"
+            )))
+        }
+        (_, Some(pick)) => Ok(places.remove(pick - 1).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn place(name: &str, admin1: &str, country: &str) -> Place {
+        Place {
+            name: name.to_string(),
+            latitude: 38.5,
+            longitude: -9.25,
+            admin1: Some(admin1.to_string()),
+            country: Some(country.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_detects_place_names() {
+        assert!(is_place_name("Lisbon, PT"));
+        assert!(is_place_name("Lisbon"));
+        assert!(!is_place_name("10001,US"));
+        assert!(!is_place_name("SW1A 1AA, GB"));
+    }
+
+    #[test]
+    fn it_filters_by_country_code_in_query() {
+        let client = Client::new(&Config::default(), "Lisbon, pt").unwrap();
+
+        assert!(client
+            .query
+            .contains(&("countryCode".to_string(), "PT".to_string())));
+        assert_eq!(client.qualifier, None);
+    }
+
+    #[test]
+    fn it_filters_by_region_name_locally() {
+        let client =
+            Client::new(&Config::default(), "Portland, Oregon").unwrap();
+        let places = client.matches(vec![
+            place("Portland", "Maine", "United States"),
+            place("Portland", "Oregon", "United States"),
+        ]);
+
+        assert_eq!(places, vec![place("Portland", "Oregon", "United States")]);
+    }
+
+    #[test]
+    fn it_also_searches_region_codes() {
+        let client = Client::new(&Config::default(), "Paris, TX").unwrap();
+        let by_region = client.by_region_code().unwrap();
+
+        assert!(!by_region.query.iter().any(|(key, _)| key == "countryCode"));
+        assert_eq!(
+            by_region.matches(vec![
+                place("Paris", "Île-de-France", "France"),
+                place("Paris", "Texas", "United States"),
+            ]),
+            vec![place("Paris", "Texas", "United States")]
+        );
+        assert!(Client::new(&Config::default(), "Paris, Texas")
+            .unwrap()
+            .by_region_code()
+            .is_none());
+        // not a region code
+        assert!(Client::new(&Config::default(), "Lisbon, PT")
+            .unwrap()
+            .by_region_code()
+            .is_none());
+    }
+
+    #[test]
+    fn it_merges_country_and_region_matches() {
+        // Paris, CA is both Paris, Canada and Paris, California
+        let client = Client::new(&Config::default(), "Paris, CA").unwrap();
+        let by_region = client.by_region_code().unwrap();
+        let ontario = place("Paris", "Ontario", "Canada");
+        let california = place("Paris", "California", "United States");
+
+        let places = merge(
+            client.matches(vec![ontario.clone()]),
+            by_region.matches(vec![
+                ontario.clone(),
+                california.clone(),
+                place("Paris", "Île-de-France", "France"),
+            ]),
+        );
+
+        assert_eq!(places, vec![ontario, california]);
+        assert!(matches!(
+            select("Paris, CA", places, None),
+            Err(Error::AmbiguousLocation { .. })
+        ));
+    }
+
+    #[test]
+    fn it_lists_each_region_code_once() {
+        for (i, (code, _)) in REGION_CODES.iter().enumerate() {
+            assert!(
+                !REGION_CODES[i + 1..].iter().any(|(other, _)| other == code),
+                "{code}"
+            );
+        }
+    }
+
+    #[test]
+    fn it_rejects_missing_name() {
+        assert!(matches!(
+            Client::new(&Config::default(), ", PT"),
+            Err(Error::InvalidLocation(_))
+        ));
+    }
+
+    #[test]
+    fn it_converts_place_to_location() {
+        let location = Location::from(Place {
+            postcodes: Some(vec!["1100-148".to_string()]),
+            ..place("Lisbon", "Lisbon", "Portugal")
+        });
+
+        assert_eq!(location.loc, "38.5,-9.25");
+        assert_eq!(location.latitude, "38.5");
+        assert_eq!(location.longitude, "-9.25");
+        assert_eq!(location.postal_code, "1100-148");
+        assert_eq!(location.name, Some("Lisbon, Lisbon, Portugal".to_string()));
+    }
+
+    #[test]
+    fn it_selects_a_single_match() {
+        let places = vec![place("Lisbon", "Lisbon", "Portugal")];
+
+        assert!(select("Lisbon", places, None).is_ok());
+    }
+
+    #[test]
+    fn it_reports_ambiguous_matches() {
+        let places = vec![
+            place("Springfield", "Illinois", "United States"),
+            place("Springfield", "Missouri", "United States"),
+        ];
+
+        match select("Springfield", places.clone(), None) {
+            Err(Error::AmbiguousLocation { candidates, .. }) => {
+                assert_eq!(
+                    candidates,
+                    vec![
+                        "Springfield, Illinois, United States",
+                        "Springfield, Missouri, United States"
+                    ]
+                );
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+
+        assert!(select("Springfield", places.clone(), Some(2)).is_ok());
+        assert!(matches!(
+            select("Springfield", places, Some(3)),
+            Err(Error::InvalidLocation(_))
+        ));
+    }
+
+    #[test]
+    fn it_reports_no_matches() {
+        assert!(matches!(
+            select("Nowhere", vec![], None),
+            Err(Error::LocationNotFound(_))
+        ));
+    }
+}
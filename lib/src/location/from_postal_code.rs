@@ -1,7 +1,14 @@
-use eyre::WrapErr;
 use serde::{Deserialize, Serialize};
 
-use super::{Location, ParseCoordinatesError};
+use super::{geodata, Location};
+use crate::{
+    api::Fetchable,
+    cache::Cache,
+    config::{Config, RequestPolicy},
+    Error, Result,
+};
+
+const PROVIDER: &str = "nominatim";
 
 // https://nominatim.openstreetmap.org/search.php?format=jsonv2&postalcode=29715
 // [
@@ -31,6 +38,8 @@ pub struct Response {
     lat: String,
     lon: String,
     name: String,
+    #[serde(default)]
+    display_name: String,
 }
 
 impl From<Response> for Location {
@@ -40,32 +49,45 @@ impl From<Response> for Location {
             latitude: response.lat.clone(),
             longitude: response.lon.clone(),
             postal_code: response.name,
+            name: Some(response.display_name).filter(|name| !name.is_empty()),
         }
     }
 }
 
 #[derive(Debug)]
 pub struct Client {
+    region: String,
+    postal_code: String,
+    country: String,
+    policy: RequestPolicy,
     query: Vec<(String, String)>,
 }
 
 impl Client {
-    pub fn new(region: &str) -> eyre::Result<Self> {
+    pub fn new(config: &Config, region: &str) -> Result<Self> {
         let (postal_code, country) =
             region.split_once(',').ok_or_else(|| {
-                eyre::eyre!("invalid location, expect [POSTAL_CODE, COUNTRY]"
-                    .to_string(),)
+                Error::InvalidLocation(
+                    "invalid location, expect [POSTAL_CODE, COUNTRY]"
+                        .to_string(),
+                )
             })?;
 
         if postal_code.is_empty() {
-            return Err(eyre::eyre!("missing postal code".to_string()));
+            return Err(Error::InvalidLocation(
+                "missing postal code".to_string(),
+            ));
         }
 
         if country.is_empty() {
-            return Err(eyre::eyre!("missing country".to_string()));
+            return Err(Error::InvalidLocation("missing country".to_string()));
         }
 
         Ok(Self {
+            region: region.to_string(),
+            postal_code: postal_code.trim().to_string(),
+            country: country.trim().to_string(),
+            policy: config.network.policy(PROVIDER),
             query: vec![
                 ("format".to_string(), "json".to_string()),
                 ("postalcode".to_string(), postal_code.to_string()),
@@ -73,29 +95,47 @@ impl Client {
             ],
         })
     }
+
+    /// Looks the postal code up among the imported `GeoNames` postal codes,
+    /// only asking Nominatim when it isn't there.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lookup fails or the postal code is unknown.
+    pub async fn locate(&self, cache: &mut Cache) -> Result<Location> {
+        if let Some(location) =
+            geodata::lookup(cache, &self.postal_code, &self.country).await?
+        {
+            log::debug!("found {} in imported geodata", self.region);
+
+            return Ok(location);
+        }
+
+        self.fetch()
+    }
 }
 
-impl crate::api::Fetchable<Response, Location> for Client {
+impl Fetchable<Response, Location> for Client {
+    fn provider(&self) -> &'static str {
+        PROVIDER
+    }
+
     fn url(&self) -> &'static str {
         "https://nominatim.openstreetmap.org/search.php"
     }
 
-    fn fetch(&self) -> eyre::Result<Location> {
-        ureq::get(self.url())
-            .query_pairs(self.query_pairs())
-            .call()
-            .map_err(|_| eyre::eyre!("unknown error"))
-            .wrap_err("error getting location from postal code")?
-            .into_json::<Vec<Response>>()
-            .wrap_err("error parsing response into Vec<Response>")?
-            .first()
-            .ok_or_else(|| {
-                ParseCoordinatesError::UnknownLocation(
-                    "error getting location from postal code".to_string(),
-                )
-            })
-            .wrap_err("error getting the first element from response")
-            .map(|location| Location::from(location.clone()))
+    fn fetch(&self) -> Result<Location> {
+        let response = self.get()?;
+
+        self.parse::<Vec<Response>>(response)?
+            .into_iter()
+            .next()
+            .map(Location::from)
+            .ok_or_else(|| Error::LocationNotFound(self.region.clone()))
+    }
+
+    fn policy(&self) -> RequestPolicy {
+        self.policy
     }
 
     fn query(&self) -> Option<&Vec<(String, String)>> {
@@ -118,6 +158,7 @@ mod tests {
             lat: lat.to_string(),
             lon: long.to_string(),
             name: name.to_string(),
+            display_name: "New York, 10001, United States".to_string(),
         };
 
         let location = Location::from(response);
@@ -126,11 +167,15 @@ mod tests {
         assert_eq!(location.latitude, lat);
         assert_eq!(location.longitude, long);
         assert_eq!(location.postal_code, name);
+        assert_eq!(
+            location.name,
+            Some("New York, 10001, United States".to_string())
+        );
     }
 
     #[test]
     fn test_client_new_valid() {
-        let result = Client::new("10001,US").unwrap();
+        let result = Client::new(&Config::default(), "10001,US").unwrap();
         let query = vec![
             ("format".to_string(), "json".to_string()),
             ("postalcode".to_string(), "10001".to_string()),
@@ -142,7 +187,7 @@ mod tests {
 
     #[test]
     fn test_client_new_invalid_format() {
-        let result = Client::new("12345");
+        let result = Client::new(&Config::default(), "12345");
 
         assert!(result.is_err());
         assert!(result
@@ -153,7 +198,7 @@ mod tests {
 
     #[test]
     fn test_client_new_missing_postal_code() {
-        let result = Client::new(",CA");
+        let result = Client::new(&Config::default(), ",CA");
 
         assert!(result.is_err());
         assert!(result
@@ -164,7 +209,7 @@ mod tests {
 
     #[test]
     fn test_client_new_missing_country() {
-        let result = Client::new("12345,");
+        let result = Client::new(&Config::default(), "12345,");
 
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("missing country"));
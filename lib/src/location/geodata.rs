@@ -0,0 +1,375 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use sqlx::FromRow;
+
+use super::Location;
+use crate::{cache::Cache, Error, Result};
+
+/// Three letter codes of the countries `GeoNames` has postal codes for, with
+/// their two letter equivalent.
+const ALPHA3_CODES: &[(&str, &str)] = &[
+    ("ALA", "AX"),
+    ("AND", "AD"),
+    ("ARG", "AR"),
+    ("ASM", "AS"),
+    ("AUS", "AU"),
+    ("AUT", "AT"),
+    ("AZE", "AZ"),
+    ("BEL", "BE"),
+    ("BGD", "BD"),
+    ("BGR", "BG"),
+    ("BLR", "BY"),
+    ("BMU", "BM"),
+    ("BRA", "BR"),
+    ("CAN", "CA"),
+    ("CHE", "CH"),
+    ("CHL", "CL"),
+    ("COL", "CO"),
+    ("CRI", "CR"),
+    ("CYP", "CY"),
+    ("CZE", "CZ"),
+    ("DEU", "DE"),
+    ("DNK", "DK"),
+    ("DOM", "DO"),
+    ("DZA", "DZ"),
+    ("ESP", "ES"),
+    ("EST", "EE"),
+    ("FIN", "FI"),
+    ("FRA", "FR"),
+    ("FRO", "FO"),
+    ("FSM", "FM"),
+    ("GBR", "GB"),
+    ("GGY", "GG"),
+    ("GLP", "GP"),
+    ("GRL", "GL"),
+    ("GTM", "GT"),
+    ("GUF", "GF"),
+    ("GUM", "GU"),
+    ("HRV", "HR"),
+    ("HTI", "HT"),
+    ("HUN", "HU"),
+    ("IMN", "IM"),
+    ("IND", "IN"),
+    ("IRL", "IE"),
+    ("ISL", "IS"),
+    ("ITA", "IT"),
+    ("JEY", "JE"),
+    ("JPN", "JP"),
+    ("KOR", "KR"),
+    ("LIE", "LI"),
+    ("LKA", "LK"),
+    ("LTU", "LT"),
+    ("LUX", "LU"),
+    ("LVA", "LV"),
+    ("MCO", "MC"),
+    ("MDA", "MD"),
+    ("MEX", "MX"),
+    ("MHL", "MH"),
+    ("MKD", "MK"),
+    ("MLT", "MT"),
+    ("MNP", "MP"),
+    ("MTQ", "MQ"),
+    ("MWI", "MW"),
+    ("MYS", "MY"),
+    ("MYT", "YT"),
+    ("NCL", "NC"),
+    ("NLD", "NL"),
+    ("NOR", "NO"),
+    ("NZL", "NZ"),
+    ("PAK", "PK"),
+    ("PER", "PE"),
+    ("PHL", "PH"),
+    ("PLW", "PW"),
+    ("POL", "PL"),
+    ("PRI", "PR"),
+    ("PRT", "PT"),
+    ("REU", "RE"),
+    ("ROU", "RO"),
+    ("RUS", "RU"),
+    ("SGP", "SG"),
+    ("SJM", "SJ"),
+    ("SMR", "SM"),
+    ("SPM", "PM"),
+    ("SRB", "RS"),
+    ("SVK", "SK"),
+    ("SVN", "SI"),
+    ("SWE", "SE"),
+    ("THA", "TH"),
+    ("TUR", "TR"),
+    ("UKR", "UA"),
+    ("URY", "UY"),
+    ("USA", "US"),
+    ("VAT", "VA"),
+    ("VIR", "VI"),
+    ("WLF", "WF"),
+    ("ZAF", "ZA"),
+];
+
+// GeoNames postal code dumps (https://download.geonames.org/export/zip/) are
+// tab separated, one postal code and place per line:
+//
+// country code, postal code, place name, admin name1, admin code1,
+// admin name2, admin code2, admin name3, admin code3, latitude, longitude,
+// accuracy
+//
+// US	10001	New York	New York	NY	New York	061			40.7484	-73.9967	4
+
+/// A postal code imported from a `GeoNames` dump.
+#[derive(Clone, Debug, PartialEq, FromRow)]
+pub struct PostalCode {
+    pub country_code: String,
+    pub postal_code: String,
+    pub place_name: String,
+    pub admin_name1: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl PostalCode {
+    /// Parses a line of a `GeoNames` dump, `None` when it's malformed.
+    fn parse(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split('\t').collect();
+
+        if fields.len() < 11 || fields[0].is_empty() || fields[1].is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            country_code: fields[0].to_uppercase(),
+            postal_code: fields[1].to_string(),
+            place_name: fields[2].to_string(),
+            admin_name1: fields[3].to_string(),
+            latitude: fields[9].parse().ok()?,
+            longitude: fields[10].parse().ok()?,
+        })
+    }
+}
+
+impl TryFrom<PostalCode> for Location {
+    type Error = Error;
+
+    fn try_from(postal_code: PostalCode) -> Result<Self> {
+        let mut location = Location::from_coordinates(
+            postal_code.latitude,
+            postal_code.longitude,
+        )?;
+        let name = [
+            postal_code.place_name,
+            postal_code.admin_name1,
+            postal_code.country_code,
+        ]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+        location.postal_code = postal_code.postal_code;
+        location.name = Some(name);
+
+        Ok(location)
+    }
+}
+
+/// Imports a `GeoNames` postal code dump (eg. `US.txt` or `allCountries.txt`
+/// from <https://download.geonames.org/export/zip/>, unzipped) into the
+/// cache, making postal code lookups for its countries work offline.
+///
+/// The postal codes already imported for the countries in the dump are
+/// replaced, malformed lines are skipped. Nothing is imported when the file
+/// can't be read to the end.
+///
+/// # Returns
+///
+/// The number of postal codes imported.
+///
+/// # Errors
+///
+/// - `Error::Read` if the file can't be read.
+/// - `Error::Cache` if the postal codes can't be stored.
+pub async fn import(cache: &mut Cache, path: &Path) -> Result<u64> {
+    let read_error = |source| Error::Read {
+        path: path.display().to_string(),
+        source,
+    };
+    let file = File::open(path).map_err(read_error)?;
+    let postal_codes =
+        BufReader::new(file).lines().filter_map(|line| {
+            match line.map_err(read_error) {
+                Ok(line) => PostalCode::parse(&line).map(Ok).or_else(|| {
+                    log::debug!("skipping malformed geonames line: {line}");
+
+                    None
+                }),
+                Err(err) => Some(Err(err)),
+            }
+        });
+
+    cache.replace_postal_codes(postal_codes).await
+}
+
+/// Looks up `postal_code` in `country` (a two or three letter country code)
+/// among the imported `GeoNames` postal codes.
+///
+/// # Errors
+///
+/// `Error::Cache` if the lookup fails.
+pub(crate) async fn lookup(
+    cache: &mut Cache,
+    postal_code: &str,
+    country: &str,
+) -> Result<Option<Location>> {
+    let Some(country) = alpha2(country) else {
+        return Ok(None);
+    };
+
+    cache
+        .find_postal_code(postal_code, &country)
+        .await?
+        .map(Location::try_from)
+        .transpose()
+}
+
+/// Normalizes a country code to the two letter, upper case, code `GeoNames`
+/// uses.
+fn alpha2(country: &str) -> Option<String> {
+    let country = country.trim().to_uppercase();
+
+    match country.len() {
+        2 => Some(country),
+        3 => ALPHA3_CODES
+            .iter()
+            .find(|(alpha3, _)| *alpha3 == country)
+            .map(|(_, alpha2)| (*alpha2).to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn it_parses_line() {
+        let line = "US\t10001\tNew York\tNew York\tNY\tNew York\t061\t\t\t40.7484\t-73.9967\t4";
+
+        assert_eq!(
+            PostalCode::parse(line),
+            Some(PostalCode {
+                country_code: "US".to_string(),
+                postal_code: "10001".to_string(),
+                place_name: "New York".to_string(),
+                admin_name1: "New York".to_string(),
+                latitude: 40.7484,
+                longitude: -73.9967,
+            })
+        );
+    }
+
+    #[test]
+    fn it_skips_malformed_lines() {
+        assert_eq!(PostalCode::parse(""), None);
+        assert_eq!(PostalCode::parse("US\t10001\tNew York"), None);
+        assert_eq!(
+            PostalCode::parse(
+                "US\t10001\tNew York\t\t\t\t\t\t\tnorth\twest\t4"
+            ),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn it_imports_and_looks_up() {
+        let dir = tempdir().unwrap();
+        let dump = dir.path().join("US.txt");
+        let mut cache =
+            Cache::new(Some(dir.path().join("test.db").display().to_string()))
+                .await
+                .unwrap();
+
+        let mut file = File::create(&dump).unwrap();
+        writeln!(file, "US\t10001\tNew York\tNew York\tNY\tNew York\t061\t\t\t40.7484\t-73.9967\t4").unwrap();
+        writeln!(file, "garbage").unwrap();
+        writeln!(file, "US\t28273\tCharlotte\tNorth Carolina\tNC\tMecklenburg\t119\t\t\t35.1287\t-80.9338\t4").unwrap();
+        drop(file);
+
+        assert_eq!(import(&mut cache, &dump).await.unwrap(), 2);
+
+        let location =
+            lookup(&mut cache, "28273", "us").await.unwrap().unwrap();
+
+        assert_eq!(location.loc, "35.1287,-80.9338");
+        assert_eq!(location.postal_code, "28273");
+        assert_eq!(
+            location.name,
+            Some("Charlotte, North Carolina, US".to_string())
+        );
+        assert!(lookup(&mut cache, "28273", "CA").await.unwrap().is_none());
+        assert_eq!(
+            lookup(&mut cache, "28273", "usa").await.unwrap(),
+            Some(location)
+        );
+        assert!(lookup(&mut cache, "28273", "xyz").await.unwrap().is_none());
+
+        // re-importing replaces rather than duplicates
+        assert_eq!(import(&mut cache, &dump).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn it_replaces_postal_codes_of_imported_countries() {
+        let dir = tempdir().unwrap();
+        let dump = dir.path().join("dump.txt");
+        let mut cache =
+            Cache::new(Some(dir.path().join("test.db").display().to_string()))
+                .await
+                .unwrap();
+        let write = |lines: &[&str]| {
+            let mut file = File::create(&dump).unwrap();
+
+            for line in lines {
+                writeln!(file, "{line}").unwrap();
+            }
+        };
+
+        write(&[
+            "US\t10001\tNew York\tNew York\tNY\t\t\t\t\t40.7484\t-73.9967\t4",
+            "US\t28273\tCharlotte\tNorth Carolina\tNC\t\t\t\t\t35.1287\t-80.9338\t4",
+            "PT\t1100-148\tLisboa\tLisboa\t\t\t\t\t\t38.71\t-9.13\t4",
+        ]);
+        assert_eq!(import(&mut cache, &dump).await.unwrap(), 3);
+
+        // a newer US dump that dropped 28273 and lists 10001 twice
+        write(&[
+            "US\t10001\tNew York\tNew York\tNY\t\t\t\t\t40.7484\t-73.9967\t4",
+            "US\t10001\tNew York City\tNew York\tNY\t\t\t\t\t40.7484\t-73.9967\t4",
+        ]);
+        assert_eq!(import(&mut cache, &dump).await.unwrap(), 1);
+
+        assert!(lookup(&mut cache, "28273", "US").await.unwrap().is_none());
+        assert!(lookup(&mut cache, "1100-148", "PT")
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn it_fails_reading_missing_file() {
+        let dir = tempdir().unwrap();
+        let mut cache =
+            Cache::new(Some(dir.path().join("test.db").display().to_string()))
+                .await
+                .unwrap();
+
+        assert!(matches!(
+            import(&mut cache, &dir.path().join("missing.txt")).await,
+            Err(Error::Read { .. })
+        ));
+    }
+}
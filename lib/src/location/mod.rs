@@ -1,20 +1,27 @@
 use std::fmt;
 
+use crate::{cache::Cache, config::Config, Error, Result};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
-use thiserror::Error;
-
-use crate::cache::Cache;
+pub(crate) mod from_coordinates;
+pub(crate) mod from_gpsd;
 pub(crate) mod from_ip;
+pub(crate) mod from_place;
 pub(crate) mod from_postal_code;
-use crate::api::Fetchable;
-
-#[derive(Error, Debug, PartialEq)]
-pub enum ParseCoordinatesError {
-    // #[error("provided string was not in the format")]
-    // InvalidFormat(String),
-    #[error("provided postal code was not found")]
-    UnknownLocation(String),
+pub mod geodata;
+mod reverse;
+
+pub use from_ip::IpProvider;
+pub use reverse::describe;
+
+/// Ways of determining where the machine is when no region is given.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LocationSource {
+    /// A fix from a local gpsd daemon.
+    Gpsd,
+    /// Geolocation of the public IP address, see `IpProvider`.
+    Ip,
 }
 
 #[derive(FromRow, Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
@@ -23,14 +30,19 @@ pub struct Location {
     pub latitude: String,
     pub longitude: String,
     pub postal_code: String,
+    /// Human readable place name, eg. `Charlotte, North Carolina, US`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
 }
 
 impl fmt::Display for Location {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         write!(
             fmt,
-            "Coordinates: {}\n  Postal Code: {}",
-            self.loc, self.postal_code,
+            "Name: {}\n  Coordinates: {}\n  Postal Code: {}",
+            self.name.as_deref().unwrap_or_default(),
+            self.loc,
+            self.postal_code,
         )
     }
 }
@@ -41,56 +53,169 @@ impl fmt::Display for Location {
 /// This function has two main branches of logic:
 ///
 /// 1. If a `region` is provided:
-///    - It first checks the cache to see if the location data for the specified
-///      region is already available.
+///    - A name prefixed with `@` (eg. `@office`) returns the location saved
+///      under that name in `config`.
+///    - A `geo:` URI (eg. `geo:35.15,-80.81`) is used as is, without any
+///      lookup.
+///    - Otherwise it first checks the cache to see if the location data for the specified
+///      region (and `pick`) is already available, ignoring case and spacing.
 ///    - If cached, it logs the cache hit and returns the cached location data.
 ///    - If not cached, it fetches the location data based on the region's
-///      postal code, caches the new data, and returns it.
+///      postal code, or searches for it by name when the region contains no
+///      digits (eg. `Lisbon, PT`), caches the new data, and returns it.
 ///
-/// 2. If no `region` is provided:
-///    - It fetches the location data based on the client's IP address, caches
-///      the new data, and returns it.
+/// 2. If no `region` is provided it tries each of `config.location_sources`
+///    in order:
+///    - `gpsd` reads a fix from the local gpsd daemon.
+///    - `ip` returns the location derived from the client's IP address cached
+///      for the current network, as long as it is younger than
+///      `config.ip_location_ttl_secs`. Otherwise it fetches the location data
+///      based on the client's IP address, caches the new data, and returns
+///      it.
 ///
 /// # Arguments
 ///
+/// - `config`: The `Config` providing the network policy for lookups.
 /// - `cache`: A mutable reference to a `Cache` object for caching location
 ///   data.
-/// - `region`: An `Option<&str>` representing a region (as a postal code). If
-///   `None`, the function will use the client's IP address to fetch location
-///   data.
+/// - `region`: An `Option<&str>` representing a region (as a postal code,
+///   place name, `geo:` URI or `@name` of a saved location). If `None`, the function will use the client's IP address to
+///   fetch location data.
+/// - `pick`: 1-based index choosing between several places matching a place
+///   name.
 ///
 /// # Returns
 ///
-/// - A `Result` with the `Location` object, or an `eyre::Error` if any step of
+/// - A `Result` with the `Location` object, or an `Error` if any step of
 ///   the process fails.
 ///
 /// # Errors
 ///
-/// This function will return an `eyre::Error` if:
+/// This function will return an `Error` if:
 ///
 /// - The location data cannot be fetched from the cache or the external service.
 /// - The cache is unable to store the new location data.
 /// - The provided region's postal code is invalid or not supported.
+/// - The provided place name matches several places and `pick` is `None`.
 /// - There is a failure in network connectivity or during the external service call.
 pub async fn get(
+    config: &Config,
     cache: &mut Cache,
     region: Option<&str>,
-) -> eyre::Result<Location> {
+    pick: Option<usize>,
+) -> Result<Location> {
     let Some(region) = region else {
-        let location = from_ip::Client::new().fetch()?;
+        return locate(config, cache).await;
+    };
+
+    if region.starts_with('@') {
+        return config.named_location(region);
+    }
+
+    if from_coordinates::is_geo_uri(region) {
+        return from_coordinates::parse(region);
+    }
 
-        cache.set(&location).await?;
+    // a pick chooses between different places for the same region
+    let alias = match pick {
+        Some(pick) => format!("{region}#{pick}"),
+        None => region.to_string(),
+    };
+
+    if let Some(location) = cache.get(&alias).await? {
+        log::debug!("using cached location for {alias}");
 
         return Ok(location);
+    }
+
+    let location = if from_place::is_place_name(region) {
+        from_place::Client::new(config, region)?.locate(pick)?
+    } else {
+        from_postal_code::Client::new(config, region)?
+            .locate(cache)
+            .await?
     };
+    let country = region.split_once(',').map_or("", |(_, country)| country);
+
+    cache.set(&alias, country, &location).await?;
 
-    let Some(location) = cache.get(region).await? else {
-        let location = from_postal_code::Client::new(region)?.fetch()?;
+    Ok(location)
+}
+
+/// Determines where the machine is, trying each of `config.location_sources`
+/// in order until one succeeds.
+///
+/// Fails with `Error::LocationUnavailable` holding the error from every
+/// source, and from every ip geolocation provider tried by the `ip` source.
+async fn locate(config: &Config, cache: &mut Cache) -> Result<Location> {
+    let mut errors = vec![];
 
-        cache.set(&location).await?;
+    for source in &config.location_sources {
+        let result = match source {
+            LocationSource::Gpsd => from_gpsd::locate(config),
+            LocationSource::Ip => from_ip_cached(config, cache).await,
+        };
+
+        match result {
+            Ok(location) => return Ok(location),
+            Err(Error::LocationUnavailable(failed)) => errors.extend(failed),
+            Err(err) => {
+                log::debug!("{source:?} location failed: {err}");
+
+                errors.push(err);
+            }
+        }
+    }
+
+    Err(Error::LocationUnavailable(errors))
+}
+
+/// Returns the IP-derived location cached for the current network when it is
+/// younger than `config.ip_location_ttl_secs`, otherwise looks it up again.
+async fn from_ip_cached(
+    config: &Config,
+    cache: &mut Cache,
+) -> Result<Location> {
+    let network = from_ip::network();
+
+    if let Some(location) = cache
+        .get_ip_location(&network, config.ip_location_ttl_secs)
+        .await?
+    {
+        log::debug!("using ip location cached for {network}");
 
         return Ok(location);
-    };
+    }
+
+    let location = from_ip::locate(config)?;
+
+    cache.set_ip_location(&network, &location).await?;
 
     Ok(location)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn it_reports_every_source_that_failed() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache =
+            Cache::new(Some(dir.path().join("test.db").display().to_string()))
+                .await
+                .unwrap();
+        // nothing listens on the discard port
+        let config = Config {
+            location_sources: vec![LocationSource::Gpsd],
+            gpsd_address: "127.0.0.1:9".to_string(),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            locate(&config, &mut cache).await,
+            Err(Error::LocationUnavailable(errors))
+                if matches!(errors.as_slice(), [Error::Network { .. }])
+        ));
+    }
+}
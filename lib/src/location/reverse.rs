@@ -0,0 +1,230 @@
+use serde::Deserialize;
+
+use super::Location;
+use crate::{
+    api::Fetchable,
+    cache::Cache,
+    config::{Config, RequestPolicy},
+};
+
+const PROVIDER: &str = "nominatim";
+
+// https://nominatim.openstreetmap.org/reverse?format=jsonv2&lat=35.15&lon=-80.81&zoom=10
+// {
+//   "place_id": 297431330,
+//   "lat": "35.2272086",
+//   "lon": "-80.8430827",
+//   "display_name": "Charlotte, Mecklenburg County, North Carolina, United States",
+//   "address": {
+//     "city": "Charlotte",
+//     "county": "Mecklenburg County",
+//     "state": "North Carolina",
+//     "country": "United States",
+//     "country_code": "us"
+//   }
+// }
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Address {
+    city: Option<String>,
+    town: Option<String>,
+    village: Option<String>,
+    hamlet: Option<String>,
+    municipality: Option<String>,
+    county: Option<String>,
+    state: Option<String>,
+    country_code: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Response {
+    display_name: String,
+    address: Address,
+}
+
+impl From<Response> for String {
+    fn from(response: Response) -> Self {
+        let address = response.address;
+        let locality = address
+            .city
+            .or(address.town)
+            .or(address.village)
+            .or(address.hamlet)
+            .or(address.municipality)
+            .or(address.county);
+        let parts: Vec<String> = [
+            locality,
+            address.state,
+            address.country_code.map(|code| code.to_uppercase()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        if parts.is_empty() {
+            response.display_name
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+pub struct Client {
+    policy: RequestPolicy,
+    query: Vec<(String, String)>,
+}
+
+impl Client {
+    pub fn new(config: &Config, location: &Location) -> Self {
+        Self {
+            policy: config.network.policy(PROVIDER),
+            query: vec![
+                ("format".to_string(), "jsonv2".to_string()),
+                ("lat".to_string(), location.latitude.clone()),
+                ("lon".to_string(), location.longitude.clone()),
+                ("zoom".to_string(), "10".to_string()),
+            ],
+        }
+    }
+}
+
+impl Fetchable<Response, String> for Client {
+    fn provider(&self) -> &'static str {
+        PROVIDER
+    }
+
+    fn url(&self) -> &'static str {
+        "https://nominatim.openstreetmap.org/reverse"
+    }
+
+    fn policy(&self) -> RequestPolicy {
+        self.policy
+    }
+
+    fn query(&self) -> Option<&Vec<(String, String)>> {
+        Some(&self.query)
+    }
+}
+
+/// Fills in the `name` of `location` from its coordinates when it is missing.
+///
+/// Names are cached by coordinates (see `Cache::set_place_name`) so that
+/// Nominatim is asked about the same place only once. This is a nicety,
+/// failures are logged and leave the name unset.
+pub async fn describe(
+    config: &Config,
+    cache: &mut Cache,
+    location: &mut Location,
+) {
+    if location.name.is_some() {
+        return;
+    }
+
+    let coordinates = location
+        .latitude
+        .parse::<f64>()
+        .ok()
+        .zip(location.longitude.parse::<f64>().ok());
+
+    if let Some((latitude, longitude)) = coordinates {
+        match cache.get_place_name(latitude, longitude).await {
+            Ok(Some(name)) => {
+                location.name = Some(name);
+
+                return;
+            }
+            Ok(None) => {}
+            Err(err) => log::warn!("unable to read cached place name: {err}"),
+        }
+    }
+
+    match Client::new(config, location).fetch() {
+        Ok(name) if !name.is_empty() => {
+            if let Some((latitude, longitude)) = coordinates {
+                if let Err(err) =
+                    cache.set_place_name(latitude, longitude, &name).await
+                {
+                    log::warn!("unable to cache place name: {err}");
+                }
+            }
+
+            location.name = Some(name);
+        }
+        Ok(_) => {}
+        Err(err) => log::warn!("unable to name {}: {err}", location.loc),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_names_from_address() {
+        let response = Response {
+            display_name: "ignored".to_string(),
+            address: Address {
+                town: Some("Fort Mill".to_string()),
+                county: Some("York County".to_string()),
+                state: Some("South Carolina".to_string()),
+                country_code: Some("us".to_string()),
+                ..Default::default()
+            },
+        };
+
+        assert_eq!(String::from(response), "Fort Mill, South Carolina, US");
+    }
+
+    #[test]
+    fn it_falls_back_to_display_name() {
+        let response = Response {
+            display_name: "Somewhere".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(String::from(response), "Somewhere");
+    }
+
+    async fn cache() -> (tempfile::TempDir, Cache) {
+        let dir = tempfile::tempdir().unwrap();
+        let cache =
+            Cache::new(Some(dir.path().join("test.db").display().to_string()))
+                .await
+                .unwrap();
+
+        (dir, cache)
+    }
+
+    #[tokio::test]
+    async fn it_keeps_existing_name() {
+        let (_dir, mut cache) = cache().await;
+        let mut location = Location {
+            name: Some("Office".to_string()),
+            ..Default::default()
+        };
+
+        describe(&Config::default(), &mut cache, &mut location).await;
+
+        assert_eq!(location.name, Some("Office".to_string()));
+    }
+
+    #[tokio::test]
+    async fn it_uses_cached_name() {
+        let (_dir, mut cache) = cache().await;
+        let mut location = Location {
+            latitude: "35.15".to_string(),
+            longitude: "-80.81".to_string(),
+            ..Default::default()
+        };
+
+        cache
+            .set_place_name(35.15, -80.81, "Charlotte")
+            .await
+            .unwrap();
+        describe(&Config::default(), &mut cache, &mut location).await;
+
+        assert_eq!(location.name, Some("Charlotte".to_string()));
+    }
+}
@@ -0,0 +1,694 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    io::Write,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    alerts::{self, Alert, Severity},
+    api::Fetchable,
+    conditions::optional,
+    config::Config,
+    location::Location,
+    weather::{open_meteo_hourly, CurrentConditions},
+    Error, Result,
+};
+
+/// A condition to be notified about in watch mode, configured as
+///
+/// ```toml
+/// [[rules]]
+/// name = "umbrella"
+/// when = "precipitation_probability"
+/// above = 70
+/// within_hours = 2
+/// ```
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Rule {
+    pub name: String,
+    #[serde(flatten)]
+    pub condition: Condition,
+    /// Command run when the rule fires, in place of `Config::hook`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+/// What a `Rule` watches, selected by its `when` key.
+///
+/// Thresholds hold when the value is strictly above `above` and strictly
+/// below `below`, setting both matches values in between.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "when", rename_all = "snake_case")]
+pub enum Condition {
+    /// The current temperature, in the configured unit.
+    Temp {
+        above: Option<f64>,
+        below: Option<f64>,
+    },
+    /// The highest hourly precipitation probability (percent) over the next
+    /// `within_hours` hours.
+    PrecipitationProbability {
+        above: Option<f64>,
+        below: Option<f64>,
+        #[serde(default = "default_within_hours")]
+        within_hours: u32,
+    },
+    /// An alert at least as severe as `severity` was issued.
+    Alert {
+        #[serde(default)]
+        severity: Severity,
+    },
+}
+
+fn default_within_hours() -> u32 {
+    2
+}
+
+/// Checks `value` against the `above` and `below` thresholds, a condition
+/// without either never holds.
+fn holds(value: f64, above: Option<f64>, below: Option<f64>) -> bool {
+    (above.is_some() || below.is_some())
+        && above.is_none_or(|above| value > above)
+        && below.is_none_or(|below| value < below)
+}
+
+/// Describes the thresholds, eg. `above 70` or `above 0 and below 10`.
+fn describe(above: Option<f64>, below: Option<f64>) -> String {
+    [
+        above.map(|above| format!("above {above}")),
+        below.map(|below| format!("below {below}")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" and ")
+}
+
+/// The values rules are evaluated against, a value is `None` when no rule
+/// needs it or it couldn't be fetched.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Snapshot {
+    /// Name of the location, or its coordinates.
+    pub location: String,
+    pub temp: Option<f64>,
+    /// Human readable condition, eg. `Partly cloudy`.
+    pub condition: Option<String>,
+    /// Hourly precipitation probabilities starting from the current hour.
+    pub precipitation_probability: Option<Vec<f64>>,
+    pub alerts: Option<Vec<Alert>>,
+}
+
+impl Snapshot {
+    /// Fetches the values needed by `rules` for `location`.
+    ///
+    /// A value that can't be fetched is left out with a warning so that the
+    /// rules depending on other values are still evaluated.
+    #[must_use]
+    pub fn get(config: &Config, location: &Location, rules: &[Rule]) -> Self {
+        let mut snapshot = Self {
+            location: location
+                .name
+                .clone()
+                .unwrap_or_else(|| location.loc.clone()),
+            ..Default::default()
+        };
+        let needs_temp = rules
+            .iter()
+            .any(|rule| matches!(rule.condition, Condition::Temp { .. }));
+        let hours = rules
+            .iter()
+            .filter_map(|rule| match rule.condition {
+                Condition::PrecipitationProbability {
+                    within_hours, ..
+                } => Some(within_hours),
+                _ => None,
+            })
+            .max();
+        let needs_alerts = rules
+            .iter()
+            .any(|rule| matches!(rule.condition, Condition::Alert { .. }));
+
+        // webhooks are told about changes of the conditions
+        if needs_temp || !config.webhooks.is_empty() {
+            if let Some(conditions) =
+                optional("conditions", CurrentConditions::get(config, location))
+            {
+                snapshot.temp =
+                    Some(config.units().temperature(conditions.temp_c.into()));
+                snapshot.condition = Some(conditions.condition);
+            }
+        }
+
+        if let Some(hours) = hours {
+            snapshot.precipitation_probability = optional(
+                "forecast",
+                open_meteo_hourly::Client::new(config, location, hours)
+                    .fetch()
+                    .map(|hourly| hourly.precipitation_probability),
+            );
+        }
+
+        if needs_alerts {
+            snapshot.alerts = optional("alerts", alerts::get(config, location));
+        }
+
+        snapshot
+    }
+}
+
+/// What an `Event` reports.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// A rule started to hold.
+    Rule,
+    /// The condition (eg. `Partly cloudy`) differs from the previous
+    /// snapshot.
+    ConditionsChanged,
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            EventKind::Rule => "rule",
+            EventKind::ConditionsChanged => "conditions_changed",
+        };
+        write!(f, "{text}")
+    }
+}
+
+/// How long a hook may run for before it is killed.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A rule firing or the conditions changing, passed to commands as JSON on
+/// stdin and to webhooks as the request body.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Event {
+    pub kind: EventKind,
+    /// Name of the rule that fired.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    pub location: String,
+    /// Human readable summary, eg. `temp 28 is below 32`.
+    pub message: String,
+    /// The value that crossed the threshold.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<f64>,
+    /// The alert that was issued.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alert: Option<Alert>,
+    /// Command to run, from the rule or `Config::hook`, `None` for
+    /// `ConditionsChanged`.
+    #[serde(skip)]
+    pub command: Option<String>,
+}
+
+impl Event {
+    /// Runs the event's command with `sh -c`, writing the event as JSON to
+    /// its stdin. The rule name and message are also available as the
+    /// `CONDITIONS_RULE` and `CONDITIONS_MESSAGE` environment variables.
+    ///
+    /// Does nothing when there is no command, the command is killed when it
+    /// runs for longer than 30 seconds.
+    ///
+    /// # Errors
+    ///
+    /// `Error::Hook` if the command can't be run, times out or exits
+    /// unsuccessfully.
+    pub fn run_hook(&self) -> Result<()> {
+        self.run_hook_within(HOOK_TIMEOUT)
+    }
+
+    fn run_hook_within(&self, timeout: Duration) -> Result<()> {
+        let Some(command) = &self.command else {
+            return Ok(());
+        };
+        let failed = |message: String| Error::Hook {
+            command: command.clone(),
+            message,
+        };
+        let json = serde_json::to_string(self)
+            .map_err(|err| failed(err.to_string()))?;
+
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .env("CONDITIONS_RULE", self.rule.as_deref().unwrap_or_default())
+            .env("CONDITIONS_MESSAGE", &self.message)
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|err| failed(err.to_string()))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            // the command may exit without reading its input
+            if let Err(err) = writeln!(stdin, "{json}") {
+                log::debug!("failed to write event to {command}: {err}");
+            }
+        }
+
+        let deadline = Instant::now() + timeout;
+        let status = loop {
+            if let Some(status) =
+                child.try_wait().map_err(|err| failed(err.to_string()))?
+            {
+                break status;
+            }
+
+            if Instant::now() >= deadline {
+                // reap the killed command so it doesn't linger as a zombie
+                let _ = child.kill();
+                let _ = child.wait();
+
+                return Err(failed(format!(
+                    "timed out after {}s",
+                    timeout.as_secs_f32()
+                )));
+            }
+
+            thread::sleep(Duration::from_millis(50));
+        };
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(failed(status.to_string()))
+        }
+    }
+}
+
+/// Evaluates rules against successive snapshots, firing each rule only when
+/// its condition starts to hold, and reports changes of the conditions.
+///
+/// Conditions are assumed not to hold before the first snapshot, so rules
+/// that already hold fire on the first one. A rule whose value is missing
+/// from a snapshot keeps its previous state.
+pub struct Watcher {
+    rules: Vec<Rule>,
+    hook: Option<String>,
+    /// Whether each threshold rule held on the last snapshot.
+    holding: Vec<bool>,
+    /// Alerts each alert rule already fired for.
+    seen: Vec<HashSet<String>>,
+    /// Condition of the last snapshot that had one.
+    condition: Option<String>,
+}
+
+impl Watcher {
+    /// Creates a watcher for `rules`, running `hook` for the rules without a
+    /// command of their own.
+    #[must_use]
+    pub fn new(rules: Vec<Rule>, hook: Option<String>) -> Self {
+        let count = rules.len();
+
+        Self {
+            rules,
+            hook,
+            holding: vec![false; count],
+            seen: vec![HashSet::new(); count],
+            condition: None,
+        }
+    }
+
+    /// Returns the events for the rules that started to hold with
+    /// `snapshot`, preceded by a `ConditionsChanged` event when the
+    /// condition differs from the previous one (the first isn't a change).
+    pub fn check(&mut self, snapshot: &Snapshot) -> Vec<Event> {
+        let mut events = vec![];
+
+        if let Some(condition) = &snapshot.condition {
+            if let Some(previous) = self
+                .condition
+                .as_ref()
+                .filter(|previous| *previous != condition)
+            {
+                events.push(Event {
+                    kind: EventKind::ConditionsChanged,
+                    rule: None,
+                    location: snapshot.location.clone(),
+                    message: format!(
+                        "conditions changed from {previous} to {condition}"
+                    ),
+                    value: snapshot.temp,
+                    alert: None,
+                    command: None,
+                });
+            }
+
+            self.condition = Some(condition.clone());
+        }
+
+        for (i, rule) in self.rules.iter().enumerate() {
+            let event = |message: String| Event {
+                kind: EventKind::Rule,
+                rule: Some(rule.name.clone()),
+                location: snapshot.location.clone(),
+                message,
+                value: None,
+                alert: None,
+                command: rule.command.clone().or_else(|| self.hook.clone()),
+            };
+
+            let (value, above, below, label) = match &rule.condition {
+                Condition::Temp { above, below } => {
+                    (snapshot.temp, *above, *below, "temp".to_string())
+                }
+                Condition::PrecipitationProbability {
+                    above,
+                    below,
+                    within_hours,
+                } => (
+                    snapshot.precipitation_probability.as_ref().and_then(
+                        |hours| {
+                            hours
+                                .iter()
+                                .take(*within_hours as usize)
+                                .copied()
+                                .reduce(f64::max)
+                        },
+                    ),
+                    *above,
+                    *below,
+                    format!(
+                        "precipitation probability in the next {within_hours}h"
+                    ),
+                ),
+                Condition::Alert { severity } => {
+                    let Some(alerts) = &snapshot.alerts else {
+                        continue;
+                    };
+                    let matching: Vec<&Alert> = alerts
+                        .iter()
+                        .filter(|alert| alert.severity >= *severity)
+                        .collect();
+
+                    for alert in &matching {
+                        if !self.seen[i].contains(&key(alert)) {
+                            events.push(Event {
+                                alert: Some((*alert).clone()),
+                                ..event(alert.headline.clone())
+                            });
+                        }
+                    }
+
+                    // forget expired alerts so that they fire if reissued
+                    self.seen[i] = matching.into_iter().map(key).collect();
+
+                    continue;
+                }
+            };
+
+            let Some(value) = value else {
+                continue;
+            };
+            let holding = holds(value, above, below);
+
+            if holding && !self.holding[i] {
+                events.push(Event {
+                    value: Some(value),
+                    ..event(format!(
+                        "{label} {value} is {}",
+                        describe(above, below)
+                    ))
+                });
+            }
+
+            self.holding[i] = holding;
+        }
+
+        events
+    }
+}
+
+/// Identifies an alert across snapshots.
+fn key(alert: &Alert) -> String {
+    format!(
+        "{}|{}|{}",
+        alert.event,
+        alert.headline,
+        alert.effective.as_deref().unwrap_or_default()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(name: &str, condition: Condition) -> Rule {
+        Rule {
+            name: name.to_string(),
+            condition,
+            command: None,
+        }
+    }
+
+    fn temp(temp: f64) -> Snapshot {
+        Snapshot {
+            location: "Charlotte".to_string(),
+            temp: Some(temp),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_parses_rules() {
+        #[derive(Deserialize)]
+        struct Rules {
+            rules: Vec<Rule>,
+        }
+
+        let rules: Rules = serde_json::from_str(
+            r#"{"rules": [
+                {"name": "freezing", "when": "temp", "below": 0},
+                {"name": "umbrella", "when": "precipitation_probability", "above": 70, "command": "notify-send umbrella"},
+                {"name": "severe", "when": "alert", "severity": "severe"}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            rules.rules,
+            vec![
+                rule(
+                    "freezing",
+                    Condition::Temp {
+                        above: None,
+                        below: Some(0.0)
+                    }
+                ),
+                Rule {
+                    command: Some("notify-send umbrella".to_string()),
+                    ..rule(
+                        "umbrella",
+                        Condition::PrecipitationProbability {
+                            above: Some(70.0),
+                            below: None,
+                            within_hours: 2,
+                        }
+                    )
+                },
+                rule(
+                    "severe",
+                    Condition::Alert {
+                        severity: Severity::Severe
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_checks_thresholds() {
+        assert!(holds(-1.0, None, Some(0.0)));
+        assert!(!holds(0.0, None, Some(0.0)));
+        assert!(holds(5.0, Some(0.0), Some(10.0)));
+        assert!(!holds(15.0, Some(0.0), Some(10.0)));
+        assert!(!holds(15.0, None, None));
+    }
+
+    #[test]
+    fn it_fires_once_per_transition() {
+        let mut watcher = Watcher::new(
+            vec![rule(
+                "freezing",
+                Condition::Temp {
+                    above: None,
+                    below: Some(32.0),
+                },
+            )],
+            Some("notify-send".to_string()),
+        );
+
+        let events = watcher.check(&temp(28.0));
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].message, "temp 28 is below 32");
+        assert_eq!(events[0].value, Some(28.0));
+        assert_eq!(events[0].command.as_deref(), Some("notify-send"));
+
+        assert!(watcher.check(&temp(27.0)).is_empty());
+        // a missing value keeps the state
+        assert!(watcher.check(&Snapshot::default()).is_empty());
+        assert!(watcher.check(&temp(35.0)).is_empty());
+        assert_eq!(watcher.check(&temp(30.0)).len(), 1);
+    }
+
+    #[test]
+    fn it_uses_precipitation_window() {
+        let mut watcher = Watcher::new(
+            vec![rule(
+                "umbrella",
+                Condition::PrecipitationProbability {
+                    above: Some(70.0),
+                    below: None,
+                    within_hours: 2,
+                },
+            )],
+            None,
+        );
+        let snapshot = |hours: Vec<f64>| Snapshot {
+            precipitation_probability: Some(hours),
+            ..Default::default()
+        };
+
+        assert!(watcher.check(&snapshot(vec![10.0, 40.0, 90.0])).is_empty());
+
+        let events = watcher.check(&snapshot(vec![40.0, 80.0, 90.0]));
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].message,
+            "precipitation probability in the next 2h 80 is above 70"
+        );
+        assert_eq!(events[0].command, None);
+    }
+
+    #[test]
+    fn it_fires_for_new_alerts() {
+        let mut watcher = Watcher::new(
+            vec![rule(
+                "severe",
+                Condition::Alert {
+                    severity: Severity::Severe,
+                },
+            )],
+            None,
+        );
+        let alert = |event: &str, severity| Alert {
+            event: event.to_string(),
+            headline: format!("{event} until 2:45PM"),
+            severity,
+            ..Default::default()
+        };
+        let snapshot = |alerts: Vec<Alert>| Snapshot {
+            alerts: Some(alerts),
+            ..Default::default()
+        };
+
+        let events = watcher.check(&snapshot(vec![
+            alert("Tornado Warning", Severity::Extreme),
+            alert("Heat Advisory", Severity::Moderate),
+        ]));
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].message, "Tornado Warning until 2:45PM");
+        assert!(events[0].alert.is_some());
+
+        assert!(watcher
+            .check(&snapshot(vec![alert("Tornado Warning", Severity::Extreme)]))
+            .is_empty());
+        assert!(watcher.check(&snapshot(vec![])).is_empty());
+        assert_eq!(
+            watcher
+                .check(&snapshot(vec![alert(
+                    "Tornado Warning",
+                    Severity::Extreme
+                )]))
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn it_reports_condition_changes() {
+        let mut watcher = Watcher::new(vec![], Some("notify-send".to_string()));
+        let snapshot = |condition: &str| Snapshot {
+            location: "Charlotte".to_string(),
+            temp: Some(71.0),
+            condition: Some(condition.to_string()),
+            ..Default::default()
+        };
+
+        assert!(watcher.check(&snapshot("Partly cloudy")).is_empty());
+        assert!(watcher.check(&snapshot("Partly cloudy")).is_empty());
+        assert!(watcher.check(&Snapshot::default()).is_empty());
+
+        let events = watcher.check(&snapshot("Slight rain"));
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, EventKind::ConditionsChanged);
+        assert_eq!(events[0].rule, None);
+        assert_eq!(
+            events[0].message,
+            "conditions changed from Partly cloudy to Slight rain"
+        );
+        assert_eq!(events[0].value, Some(71.0));
+        // local hooks are only run for rules
+        assert_eq!(events[0].command, None);
+    }
+
+    #[test]
+    fn it_runs_hook_with_event_on_stdin() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("event.json");
+        let event = Event {
+            kind: EventKind::Rule,
+            rule: Some("freezing".to_string()),
+            location: "Charlotte".to_string(),
+            message: "temp 28 is below 32".to_string(),
+            value: Some(28.0),
+            alert: None,
+            command: Some(format!(
+                "cat > {} && test \"$CONDITIONS_RULE\" = freezing",
+                path.display()
+            )),
+        };
+
+        event.run_hook().unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "{\"kind\":\"rule\",\"rule\":\"freezing\",\"location\":\"Charlotte\",\"message\":\"temp 28 is below 32\",\"value\":28.0}\n"
+        );
+
+        let event = Event {
+            command: Some("exit 3".to_string()),
+            ..event
+        };
+
+        assert!(matches!(event.run_hook(), Err(Error::Hook { .. })));
+    }
+
+    #[test]
+    fn it_kills_hooks_that_time_out() {
+        let event = Event {
+            kind: EventKind::Rule,
+            rule: Some("freezing".to_string()),
+            location: "Charlotte".to_string(),
+            message: "temp 28 is below 32".to_string(),
+            value: Some(28.0),
+            alert: None,
+            command: Some("exec sleep 10".to_string()),
+        };
+        let started = Instant::now();
+
+        let result = event.run_hook_within(Duration::from_millis(100));
+
+        assert!(matches!(result, Err(Error::Hook { .. })));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,431 @@
+use std::fmt;
+
+use serde::{
+    de::{self, Unexpected},
+    Deserialize, Deserializer, Serialize,
+};
+
+use crate::{weather::derived, Unit};
+
+/// Presets for the units of every quantity.
+///
+/// | system     | temperature | wind | pressure | precipitation | visibility |
+/// |------------|-------------|------|----------|---------------|------------|
+/// | `metric`   | °C          | km/h | hPa      | mm            | km         |
+/// | `imperial` | °F          | mph  | inHg     | in            | mi         |
+/// | `uk`       | °C          | mph  | hPa      | mm            | mi         |
+/// | `si`       | °C          | m/s  | hPa      | mm            | km         |
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum System {
+    Metric,
+    Imperial,
+    Uk,
+    Si,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Wind {
+    /// km/h
+    Kmh,
+    /// m/s
+    Ms,
+    Mph,
+    Knots,
+    /// Force on the Beaufort scale, from 0 to 12.
+    Beaufort,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pressure {
+    Hpa,
+    Inhg,
+    Mmhg,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Precipitation {
+    Mm,
+    In,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Distance {
+    Km,
+    Mi,
+}
+
+/// How `Output::temp` is rounded to `Config::precision` decimals.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rounding {
+    #[default]
+    Nearest,
+    Floor,
+    Ceil,
+    Truncate,
+}
+
+impl Rounding {
+    #[must_use]
+    pub fn round(self, value: f64, precision: u8) -> f64 {
+        let scale = 10_f64.powi(i32::from(precision));
+        let mut scaled = value * scale;
+        // 71.9 * 10 is a hair above 719, which mustn't ceil to 720
+        if (scaled - scaled.round()).abs() < 1e-9 {
+            scaled = scaled.round();
+        }
+
+        let rounded = match self {
+            Rounding::Nearest => scaled.round(),
+            Rounding::Floor => scaled.floor(),
+            Rounding::Ceil => scaled.ceil(),
+            Rounding::Truncate => scaled.trunc(),
+        };
+
+        rounded / scale
+    }
+}
+
+/// Units as set in the configuration file, every quantity left out follows
+/// `system`:
+///
+/// ```toml
+/// [units]
+/// system = "metric"
+/// wind = "knots"
+/// ```
+///
+/// Without a `system` the top level `unit` picks `metric` (`C`) or
+/// `imperial` (`F`), and the temperature keeps following it.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct UnitsConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<System>,
+    #[serde(
+        deserialize_with = "temperature",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub temperature: Option<Unit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wind: Option<Wind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pressure: Option<Pressure>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precipitation: Option<Precipitation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<Distance>,
+}
+
+/// Parses the temperature override, unlike the top level `unit` (which falls
+/// back to `F`) unknown values are rejected like those of the other
+/// quantities.
+fn temperature<'de, D>(deserializer: D) -> Result<Option<Unit>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Some(unit) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+
+    match unit.to_lowercase().as_str() {
+        "c" | "celsius" => Ok(Some(Unit::C)),
+        "f" | "fahrenheit" => Ok(Some(Unit::F)),
+        _ => Err(de::Error::invalid_value(
+            Unexpected::Str(&unit),
+            &"c, celsius, f or fahrenheit",
+        )),
+    }
+}
+
+impl UnitsConfig {
+    /// Checks that no quantity is set, so that the table can be left out of
+    /// the configuration file.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The units to use, `unit` being the top level temperature unit.
+    #[must_use]
+    pub fn resolve(&self, unit: Unit) -> Units {
+        let system = self.system.unwrap_or(match unit {
+            Unit::C => System::Metric,
+            Unit::F => System::Imperial,
+        });
+        let preset = system.units();
+
+        Units {
+            temperature: self.temperature.unwrap_or(if self.system.is_some() {
+                preset.temperature
+            } else {
+                unit
+            }),
+            wind: self.wind.unwrap_or(preset.wind),
+            pressure: self.pressure.unwrap_or(preset.pressure),
+            precipitation: self.precipitation.unwrap_or(preset.precipitation),
+            visibility: self.visibility.unwrap_or(preset.visibility),
+        }
+    }
+}
+
+/// The unit of each quantity in `Output`, providers report them in metric
+/// units which are converted by the library.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Units {
+    pub temperature: Unit,
+    pub wind: Wind,
+    pub pressure: Pressure,
+    pub precipitation: Precipitation,
+    pub visibility: Distance,
+}
+
+impl Default for Units {
+    /// Follows the default `Unit`.
+    fn default() -> Self {
+        UnitsConfig::default().resolve(Unit::default())
+    }
+}
+
+impl System {
+    #[must_use]
+    pub fn units(self) -> Units {
+        match self {
+            System::Metric => Units {
+                temperature: Unit::C,
+                wind: Wind::Kmh,
+                pressure: Pressure::Hpa,
+                precipitation: Precipitation::Mm,
+                visibility: Distance::Km,
+            },
+            System::Imperial => Units {
+                temperature: Unit::F,
+                wind: Wind::Mph,
+                pressure: Pressure::Inhg,
+                precipitation: Precipitation::In,
+                visibility: Distance::Mi,
+            },
+            System::Uk => Units {
+                temperature: Unit::C,
+                wind: Wind::Mph,
+                pressure: Pressure::Hpa,
+                precipitation: Precipitation::Mm,
+                visibility: Distance::Mi,
+            },
+            System::Si => Units {
+                temperature: Unit::C,
+                wind: Wind::Ms,
+                pressure: Pressure::Hpa,
+                precipitation: Precipitation::Mm,
+                visibility: Distance::Km,
+            },
+        }
+    }
+}
+
+impl Units {
+    #[must_use]
+    pub fn temperature(&self, celsius: f64) -> f64 {
+        match self.temperature {
+            Unit::C => celsius,
+            Unit::F => celsius * 9.0 / 5.0 + 32.0,
+        }
+    }
+
+    #[must_use]
+    pub fn wind(&self, kmh: f64) -> f64 {
+        match self.wind {
+            Wind::Kmh => kmh,
+            Wind::Ms => kmh / 3.6,
+            Wind::Mph => kmh / 1.609_344,
+            Wind::Knots => kmh / 1.852,
+            // the scale is coarser than the lost precision
+            #[allow(clippy::cast_possible_truncation)]
+            Wind::Beaufort => f64::from(derived::beaufort(kmh as f32)),
+        }
+    }
+
+    #[must_use]
+    pub fn pressure(&self, hpa: f64) -> f64 {
+        match self.pressure {
+            Pressure::Hpa => hpa,
+            Pressure::Inhg => hpa / 33.863_89,
+            Pressure::Mmhg => hpa / 1.333_224,
+        }
+    }
+
+    #[must_use]
+    pub fn precipitation(&self, mm: f64) -> f64 {
+        match self.precipitation {
+            Precipitation::Mm => mm,
+            Precipitation::In => mm / 25.4,
+        }
+    }
+
+    #[must_use]
+    pub fn visibility(&self, km: f64) -> f64 {
+        match self.visibility {
+            Distance::Km => km,
+            Distance::Mi => km / 1.609_344,
+        }
+    }
+}
+
+impl fmt::Display for Units {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let wind = match self.wind {
+            Wind::Kmh => "km/h",
+            Wind::Ms => "m/s",
+            Wind::Mph => "mph",
+            Wind::Knots => "kn",
+            Wind::Beaufort => "Bft",
+        };
+        let pressure = match self.pressure {
+            Pressure::Hpa => "hPa",
+            Pressure::Inhg => "inHg",
+            Pressure::Mmhg => "mmHg",
+        };
+        let precipitation = match self.precipitation {
+            Precipitation::Mm => "mm",
+            Precipitation::In => "in",
+        };
+        let visibility = match self.visibility {
+            Distance::Km => "km",
+            Distance::Mi => "mi",
+        };
+
+        write!(
+            f,
+            "{}, {wind}, {pressure}, {precipitation}, {visibility}",
+            self.temperature
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 0.01,
+            "{actual} is not close to {expected}"
+        );
+    }
+
+    #[test]
+    fn it_follows_unit_without_system() {
+        let units = UnitsConfig::default().resolve(Unit::C);
+
+        assert_eq!(units, System::Metric.units());
+        assert_eq!(
+            UnitsConfig::default().resolve(Unit::F),
+            System::Imperial.units()
+        );
+    }
+
+    #[test]
+    fn it_applies_system_and_overrides() {
+        let config: UnitsConfig =
+            serde_json::from_str(r#"{"system": "metric", "wind": "knots"}"#)
+                .unwrap();
+
+        let units = config.resolve(Unit::F);
+
+        assert_eq!(units.temperature, Unit::C);
+        assert_eq!(units.wind, Wind::Knots);
+        assert_eq!(units.pressure, Pressure::Hpa);
+    }
+
+    #[test]
+    fn it_parses_temperature_override() {
+        for (value, unit) in [
+            ("c", Unit::C),
+            ("C", Unit::C),
+            ("celsius", Unit::C),
+            ("F", Unit::F),
+            ("fahrenheit", Unit::F),
+        ] {
+            let config: UnitsConfig = serde_json::from_str(&format!(
+                r#"{{"temperature": "{value}"}}"#
+            ))
+            .unwrap();
+
+            assert_eq!(config.temperature, Some(unit), "{value}");
+        }
+
+        assert!(serde_json::from_str::<UnitsConfig>(
+            r#"{"temperature": "celcius"}"#
+        )
+        .is_err());
+        assert_eq!(
+            serde_json::from_str::<UnitsConfig>("{}")
+                .unwrap()
+                .temperature,
+            None
+        );
+    }
+
+    #[test]
+    fn it_keeps_temperature_override() {
+        let config = UnitsConfig {
+            system: Some(System::Uk),
+            temperature: Some(Unit::F),
+            ..Default::default()
+        };
+
+        assert_eq!(config.resolve(Unit::C).temperature, Unit::F);
+    }
+
+    #[test]
+    fn it_converts_quantities() {
+        let metric = System::Metric.units();
+        let imperial = System::Imperial.units();
+        let knots = Units {
+            wind: Wind::Knots,
+            pressure: Pressure::Mmhg,
+            ..System::Si.units()
+        };
+
+        assert_near(imperial.temperature(100.0), 212.0);
+        assert_near(imperial.wind(100.0), 62.14);
+        assert_near(imperial.pressure(1013.25), 29.92);
+        assert_near(imperial.precipitation(25.4), 1.0);
+        assert_near(imperial.visibility(10.0), 6.21);
+        assert_near(metric.wind(36.0), 36.0);
+        assert_near(knots.wind(18.52), 10.0);
+        assert_near(knots.pressure(1013.25), 760.0);
+        assert_near(System::Si.units().wind(36.0), 10.0);
+        assert_near(
+            Units {
+                wind: Wind::Beaufort,
+                ..metric
+            }
+            .wind(40.0),
+            6.0,
+        );
+    }
+
+    #[test]
+    fn it_rounds_as_configured() {
+        assert_near(Rounding::Nearest.round(71.9, 0), 72.0);
+        assert_near(Rounding::Nearest.round(-3.5, 0), -4.0);
+        assert_near(Rounding::Floor.round(71.9, 0), 71.0);
+        assert_near(Rounding::Floor.round(-3.2, 0), -4.0);
+        assert_near(Rounding::Ceil.round(71.2, 0), 72.0);
+        assert_near(Rounding::Ceil.round(71.9, 1), 71.9);
+        assert_near(Rounding::Truncate.round(71.9, 0), 71.0);
+        assert_near(Rounding::Truncate.round(-3.7, 0), -3.0);
+        assert_near(Rounding::Nearest.round(71.86, 1), 71.9);
+    }
+
+    #[test]
+    fn it_displays_units() {
+        assert_eq!(System::Uk.units().to_string(), "celsius, mph, hPa, mm, mi");
+    }
+}
@@ -0,0 +1,119 @@
+use serde::Serialize;
+
+use crate::conditions::Output;
+
+/// Shown in place of the weather icon while an alert is active.
+pub const ALERT_ICON: &str = "󰀦"; // alert
+
+/// `Output` in the JSON format of a waybar custom module (`return-type` set
+/// to `json`).
+///
+/// While alerts are active the icon is replaced with `ALERT_ICON`, the
+/// headlines are listed in the tooltip and the module gets the `alert` and
+/// `alert-<severity>` (of the most severe alert) CSS classes. When the alerts
+/// couldn't be fetched the module gets the `alerts-unavailable` class so that
+/// a missing warning doesn't go unnoticed.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Waybar {
+    pub text: String,
+    pub tooltip: String,
+    pub class: Vec<String>,
+}
+
+impl From<&Output> for Waybar {
+    fn from(output: &Output) -> Self {
+        let mut icon = output.icon.trim_end();
+        let mut tooltip = vec![];
+        let mut class = vec![];
+
+        if let Some(name) =
+            output.location.as_ref().and_then(|l| l.name.as_ref())
+        {
+            tooltip.push(name.clone());
+        }
+
+        if let Some(alerts) = &output.alerts {
+            // alerts are sorted from most to least severe
+            if let Some(alert) = alerts.first() {
+                icon = ALERT_ICON;
+                class.push("alert".to_string());
+                class.push(format!("alert-{}", alert.severity));
+            }
+
+            tooltip.extend(alerts.iter().map(|alert| alert.headline.clone()));
+        } else {
+            tooltip.push("alerts unavailable".to_string());
+            class.push("alerts-unavailable".to_string());
+        }
+
+        Self {
+            text: format!("{icon} {}°", output.temp),
+            tooltip: tooltip.join("\n"),
+            class,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::{Alert, Severity};
+
+    fn output(alerts: Option<Vec<Alert>>) -> Output {
+        Output {
+            temp: 72.0,
+            icon: "\u{e30d} ".to_string(),
+            alerts,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_formats_without_alerts() {
+        assert_eq!(
+            Waybar::from(&output(Some(vec![]))),
+            Waybar {
+                text: "\u{e30d} 72°".to_string(),
+                tooltip: String::new(),
+                class: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn it_flags_active_alerts() {
+        let alerts = vec![
+            Alert {
+                event: "Tornado Warning".to_string(),
+                headline: "Tornado Warning until 2:45PM".to_string(),
+                severity: Severity::Extreme,
+                ..Default::default()
+            },
+            Alert {
+                event: "Heat Advisory".to_string(),
+                headline: "Heat Advisory until 8:00PM".to_string(),
+                severity: Severity::Moderate,
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(
+            Waybar::from(&output(Some(alerts))),
+            Waybar {
+                text: format!("{ALERT_ICON} 72°"),
+                tooltip:
+                    "Tornado Warning until 2:45PM\nHeat Advisory until 8:00PM"
+                        .to_string(),
+                class: vec!["alert".to_string(), "alert-extreme".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn it_flags_unavailable_alerts() {
+        let waybar = Waybar::from(&output(None));
+
+        assert_eq!(waybar.text, "\u{e30d} 72°");
+        assert_eq!(waybar.class, vec!["alerts-unavailable".to_string()]);
+    }
+}
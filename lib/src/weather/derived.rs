@@ -0,0 +1,176 @@
+//! Quantities derived from the temperature, relative humidity and wind speed,
+//! so that they are available whichever provider reported the conditions.
+//!
+//! Temperatures are in °C, relative humidity in percent and wind speed in
+//! km/h.
+
+/// Upper bounds (m/s) of Beaufort forces 0 to 11, anything faster is 12.
+const BEAUFORT_SCALE: [f32; 12] = [
+    0.5, 1.6, 3.4, 5.5, 8.0, 10.8, 13.9, 17.2, 20.8, 24.5, 28.5, 32.7,
+];
+
+/// Dew point using the Magnus formula with the Alduchov and Eskridge
+/// coefficients, within 0.4 °C from -40 °C to 50 °C.
+#[must_use]
+pub fn dew_point(temp: f32, humidity: f32) -> f32 {
+    const B: f32 = 17.625;
+    const C: f32 = 243.04;
+
+    let gamma = (humidity.max(1.0) / 100.0).ln() + B * temp / (C + temp);
+
+    C * gamma / (B - gamma)
+}
+
+/// Heat index as computed by the US National Weather Service, see
+/// <https://www.wpc.ncep.noaa.gov/html/heatindex_equation.shtml>. Close to
+/// the temperature when it is cool.
+#[must_use]
+pub fn heat_index(temp: f32, humidity: f32) -> f32 {
+    let t = to_fahrenheit(temp);
+    let rh = humidity;
+    let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+
+    if f32::midpoint(simple, t) < 80.0 {
+        return to_celsius(simple);
+    }
+
+    let mut index = -42.379 + 2.049_015_2 * t + 10.143_331 * rh
+        - 0.224_755_4 * t * rh
+        - 0.006_837_83 * t * t
+        - 0.054_817_17 * rh * rh
+        + 0.001_228_74 * t * t * rh
+        + 0.000_852_82 * t * rh * rh
+        - 0.000_001_99 * t * t * rh * rh;
+
+    if rh < 13.0 && (80.0..=112.0).contains(&t) {
+        index -= (13.0 - rh) / 4.0 * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+    } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+        index += (rh - 85.0) / 10.0 * (87.0 - t) / 5.0;
+    }
+
+    to_celsius(index)
+}
+
+/// Wind chill using the formula shared by Environment Canada and the US
+/// National Weather Service. It is only defined at or below 10 °C with wind
+/// above 4.8 km/h, otherwise it is the temperature.
+#[must_use]
+pub fn wind_chill(temp: f32, wind_kph: f32) -> f32 {
+    if temp > 10.0 || wind_kph <= 4.8 {
+        return temp;
+    }
+
+    let wind = wind_kph.powf(0.16);
+
+    13.12 + 0.6215 * temp - 11.37 * wind + 0.3965 * temp * wind
+}
+
+/// Humidex as used by Environment Canada, see
+/// <https://en.wikipedia.org/wiki/Humidex>.
+#[must_use]
+pub fn humidex(temp: f32, humidity: f32) -> f32 {
+    let dew_point = dew_point(temp, humidity);
+    let vapour_pressure =
+        6.11 * (5417.753 * (1.0 / 273.16 - 1.0 / (273.15 + dew_point))).exp();
+
+    temp + 0.5555 * (vapour_pressure - 10.0)
+}
+
+/// Wet-bulb temperature using Stull's empirical formula, within 1 °C for
+/// relative humidity from 5% to 99% and temperatures from -20 °C to 50 °C.
+#[must_use]
+pub fn wet_bulb(temp: f32, humidity: f32) -> f32 {
+    let rh = humidity;
+
+    temp * (0.151_977 * (rh + 8.313_659).sqrt()).atan() + (temp + rh).atan()
+        - (rh - 1.676_331).atan()
+        + 0.003_918_38 * rh.powf(1.5) * (0.023_101 * rh).atan()
+        - 4.686_035
+}
+
+/// Force on the Beaufort scale, from 0 (calm) to 12 (hurricane).
+#[must_use]
+pub fn beaufort(wind_kph: f32) -> u8 {
+    let wind = wind_kph / 3.6;
+    let force = BEAUFORT_SCALE
+        .iter()
+        .position(|&bound| wind < bound)
+        .unwrap_or(BEAUFORT_SCALE.len());
+
+    u8::try_from(force).unwrap_or(u8::MAX)
+}
+
+#[must_use]
+pub fn to_fahrenheit(temp: f32) -> f32 {
+    temp * 9.0 / 5.0 + 32.0
+}
+
+#[must_use]
+pub fn to_celsius(temp: f32) -> f32 {
+    (temp - 32.0) * 5.0 / 9.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: f32, expected: f32, tolerance: f32) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "{actual} is not within {tolerance} of {expected}"
+        );
+    }
+
+    #[test]
+    fn it_computes_dew_point() {
+        assert_near(dew_point(25.0, 60.0), 16.7, 0.1);
+        assert_near(dew_point(0.0, 100.0), 0.0, 0.01);
+    }
+
+    #[test]
+    fn it_computes_heat_index() {
+        // 90 °F at 70% is 106 °F according to the NWS chart
+        assert_near(
+            to_fahrenheit(heat_index(to_celsius(90.0), 70.0)),
+            106.0,
+            0.5,
+        );
+        // the simple formula applies when it is mild
+        assert_near(heat_index(20.0, 50.0), 19.4, 0.1);
+    }
+
+    #[test]
+    fn it_computes_wind_chill() {
+        // -10 °C at 30 km/h is -20 according to Environment Canada's chart
+        assert_near(wind_chill(-10.0, 30.0), -19.5, 0.1);
+        assert_near(wind_chill(15.0, 30.0), 15.0, f32::EPSILON);
+        assert_near(wind_chill(-10.0, 3.0), -10.0, f32::EPSILON);
+    }
+
+    #[test]
+    fn it_computes_humidex() {
+        // 30 °C with a dew point of 15 °C is 34 according to Environment
+        // Canada's table
+        assert_near(humidex(30.0, 39.8), 34.0, 0.5);
+    }
+
+    #[test]
+    fn it_computes_wet_bulb() {
+        // Stull's own example
+        assert_near(wet_bulb(20.0, 50.0), 13.7, 0.1);
+    }
+
+    #[test]
+    fn it_computes_beaufort() {
+        assert_eq!(beaufort(0.0), 0);
+        assert_eq!(beaufort(10.0), 2);
+        assert_eq!(beaufort(40.0), 6);
+        assert_eq!(beaufort(250.0), 12);
+    }
+
+    #[test]
+    fn it_converts_temperatures() {
+        assert_near(to_fahrenheit(100.0), 212.0, f32::EPSILON);
+        assert_near(to_celsius(-40.0), -40.0, f32::EPSILON);
+    }
+}
@@ -1,8 +1,14 @@
 use std::fmt;
 
-use crate::{api::Fetchable, config::Config, location::Location};
+use crate::{
+    air_quality::AirQuality, api::Fetchable, config::Config,
+    location::Location, Error, Result,
+};
 
+pub(crate) mod derived;
 pub(crate) mod open_meteo;
+pub(crate) mod open_meteo_archive;
+pub(crate) mod open_meteo_hourly;
 pub(crate) mod weather_api;
 
 #[derive(Debug)]
@@ -10,14 +16,40 @@ pub struct CurrentConditions {
     pub temp_c: f32,
     pub temp_f: f32,
     pub icon: String,
+    /// The provider that reported the conditions.
+    pub source: Source,
+    /// Provider specific condition code (WMO code for Open-Meteo).
+    pub code: i32,
+    /// Human readable condition, eg. `Partly cloudy`.
+    pub condition: String,
+    pub is_day: bool,
+    /// Relative humidity, in percent.
+    pub humidity: Option<f32>,
+    pub wind_kph: Option<f32>,
+    pub pressure_hpa: Option<f32>,
+    pub precip_mm: Option<f32>,
+    pub visibility_km: Option<f32>,
+    pub derived: Derived,
+    /// Air quality reported along with the conditions, requested from
+    /// providers that support it when `Config::show_air_quality` is set.
+    pub air_quality: Option<AirQuality>,
 }
 
-pub trait Provider {
-    fn current(&self) -> eyre::Result<CurrentConditions>;
-    fn query_pairs(&self) -> Vec<(&str, &str)>;
+/// Quantities derived from the temperature, humidity and wind speed, as
+/// reported by the provider or computed by `CurrentConditions::get` when it
+/// omitted them. Each is `None` only when the values it is derived from are
+/// missing too.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Derived {
+    pub dew_point_c: Option<f32>,
+    pub heat_index_c: Option<f32>,
+    pub wind_chill_c: Option<f32>,
+    pub humidex: Option<f32>,
+    pub wet_bulb_c: Option<f32>,
+    pub beaufort: Option<u8>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Source {
     WeatherAPI,
     OpenMeteo,
@@ -37,8 +69,9 @@ impl CurrentConditions {
     pub fn get(
         config: &Config,
         location: &Location,
-    ) -> eyre::Result<CurrentConditions> {
+    ) -> Result<CurrentConditions> {
         let sources = vec![Source::WeatherAPI, Source::OpenMeteo];
+        let mut errors = vec![];
 
         for source in &sources {
             let result = match source {
@@ -50,12 +83,46 @@ impl CurrentConditions {
                 }
             };
 
-            if result.is_ok() {
-                return result;
+            match result {
+                Ok(conditions) => return Ok(conditions.derive()),
+                Err(err) => errors.push(err),
             }
         }
 
-        Err(eyre::eyre!("no weather providers succeeded"))
+        Err(Error::AllProvidersFailed(errors))
+    }
+
+    /// Computes the derived quantities the provider omitted, so that they
+    /// don't depend on which provider reported the conditions.
+    fn derive(mut self) -> Self {
+        let temp = self.temp_c;
+        let metrics = &mut self.derived;
+
+        if let Some(humidity) = self.humidity {
+            metrics
+                .dew_point_c
+                .get_or_insert_with(|| derived::dew_point(temp, humidity));
+            metrics
+                .heat_index_c
+                .get_or_insert_with(|| derived::heat_index(temp, humidity));
+            metrics
+                .humidex
+                .get_or_insert_with(|| derived::humidex(temp, humidity));
+            metrics
+                .wet_bulb_c
+                .get_or_insert_with(|| derived::wet_bulb(temp, humidity));
+        }
+
+        if let Some(wind_kph) = self.wind_kph {
+            metrics
+                .wind_chill_c
+                .get_or_insert_with(|| derived::wind_chill(temp, wind_kph));
+            metrics
+                .beaufort
+                .get_or_insert_with(|| derived::beaufort(wind_kph));
+        }
+
+        self
     }
 }
 
@@ -63,6 +130,60 @@ impl CurrentConditions {
 mod test {
     use super::*;
 
+    fn conditions(derived: Derived) -> CurrentConditions {
+        CurrentConditions {
+            temp_c: 30.0,
+            temp_f: 86.0,
+            icon: "icon".to_string(),
+            source: Source::OpenMeteo,
+            code: 0,
+            condition: "Clear sky".to_string(),
+            is_day: true,
+            humidity: Some(40.0),
+            wind_kph: Some(20.0),
+            pressure_hpa: None,
+            precip_mm: None,
+            visibility_km: None,
+            derived,
+            air_quality: None,
+        }
+    }
+
+    #[test]
+    fn it_derives_omitted_quantities() {
+        let conditions = conditions(Derived::default()).derive();
+
+        assert!(conditions.derived.dew_point_c.is_some());
+        assert!(conditions.derived.heat_index_c.is_some());
+        assert!(conditions.derived.humidex.is_some());
+        assert!(conditions.derived.wet_bulb_c.is_some());
+        assert_eq!(conditions.derived.wind_chill_c, Some(30.0));
+        assert_eq!(conditions.derived.beaufort, Some(4));
+    }
+
+    #[test]
+    fn it_keeps_reported_quantities() {
+        let conditions = conditions(Derived {
+            dew_point_c: Some(14.0),
+            heat_index_c: Some(31.0),
+            ..Default::default()
+        })
+        .derive();
+
+        assert_eq!(conditions.derived.dew_point_c, Some(14.0));
+        assert_eq!(conditions.derived.heat_index_c, Some(31.0));
+    }
+
+    #[test]
+    fn it_leaves_quantities_without_inputs_out() {
+        let mut conditions = conditions(Derived::default());
+
+        conditions.humidity = None;
+        conditions.wind_kph = None;
+
+        assert_eq!(conditions.derive().derived, Derived::default());
+    }
+
     #[test]
     fn it_converts_provider_to_string() {
         assert_eq!(Source::WeatherAPI.to_string(), "WeatherAPI");
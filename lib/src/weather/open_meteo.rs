@@ -1,7 +1,15 @@
 use serde::Deserialize;
 
-use super::{CurrentConditions, Source};
-use crate::{icons::TimeOfDay, location::Location, Config};
+use super::{derived::to_fahrenheit, CurrentConditions, Derived, Source};
+use crate::{
+    config::RequestPolicy, icons::TimeOfDay, location::Location, Config,
+};
+
+const PROVIDER: &str = "open_meteo";
+
+/// Reported in metric units, converted by `crate::units::Units`.
+const CURRENT: &str =
+    "relative_humidity_2m,pressure_msl,precipitation,visibility";
 
 // {
 //   "latitude": 35.159126,
@@ -11,8 +19,16 @@ use crate::{icons::TimeOfDay, location::Location, Config};
 //   "timezone": "GMT",
 //   "timezone_abbreviation": "GMT",
 //   "elevation": 219,
+//   "current": {
+//     "time": "2023-08-14T19:00",
+//     "interval": 900,
+//     "relative_humidity_2m": 41,
+//     "pressure_msl": 1016.2,
+//     "precipitation": 0.0,
+//     "visibility": 24140.0
+//   },
 //   "current_weather": {
-//     "temperature": 95.9,
+//     "temperature": 35.5,
 //     "windspeed": 15.5,
 //     "winddirection": 245,
 //     "weathercode": 0,
@@ -22,17 +38,17 @@ use crate::{icons::TimeOfDay, location::Location, Config};
 // }
 //
 pub struct Client {
+    policy: RequestPolicy,
     query: Vec<(String, String)>,
 }
 
 impl Client {
     pub fn new(config: &Config, location: &Location) -> Self {
-        let unit = config.unit.to_string();
-
         Self {
+            policy: config.network.policy(PROVIDER),
             query: vec![
                 ("current_weather".to_string(), "true".to_string()),
-                ("temperature_unit".to_string(), unit),
+                ("current".to_string(), CURRENT.to_string()),
                 ("latitude".to_string(), location.latitude.clone()),
                 ("longitude".to_string(), location.longitude.clone()),
             ],
@@ -42,21 +58,55 @@ impl Client {
 
 #[derive(Debug, Deserialize)]
 struct CurrentWeather {
+    /// °C
     temperature: f32,
+    /// km/h
+    #[serde(default)]
+    windspeed: Option<f32>,
     weathercode: i32,
-    is_day: u8,
+    #[serde(default)]
+    is_day: Option<u8>,
+    /// UTC as no timezone is requested, eg. `2023-08-14T19:00`.
+    #[serde(default)]
+    time: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Current {
+    relative_humidity_2m: Option<f32>,
+    /// hPa
+    pressure_msl: Option<f32>,
+    /// mm
+    precipitation: Option<f32>,
+    /// m
+    visibility: Option<f32>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Response {
+    #[serde(default)]
+    latitude: Option<f64>,
+    #[serde(default)]
+    longitude: Option<f64>,
+    #[serde(default)]
+    current: Current,
     current_weather: CurrentWeather,
 }
 
 impl crate::api::Fetchable<Response, CurrentConditions> for Client {
+    fn provider(&self) -> &'static str {
+        PROVIDER
+    }
+
     fn url(&self) -> &'static str {
         "https://api.open-meteo.com/v1/forecast"
     }
 
+    fn policy(&self) -> RequestPolicy {
+        self.policy
+    }
+
     fn query(&self) -> Option<&Vec<(String, String)>> {
         Some(&self.query)
     }
@@ -64,17 +114,78 @@ impl crate::api::Fetchable<Response, CurrentConditions> for Client {
 
 impl From<Response> for CurrentConditions {
     fn from(result: Response) -> Self {
-        let icon = TimeOfDay::from(result.current_weather.is_day)
+        let time_of_day = TimeOfDay::reported(
+            result.current_weather.is_day,
+            result.latitude,
+            result.longitude,
+            result
+                .current_weather
+                .time
+                .as_deref()
+                .and_then(crate::astro::parse_time)
+                .unwrap_or_else(crate::astro::now),
+        );
+        let icon = time_of_day
             .icon(&Source::OpenMeteo, result.current_weather.weathercode);
 
         Self {
             temp_c: result.current_weather.temperature,
-            temp_f: result.current_weather.temperature,
+            temp_f: to_fahrenheit(result.current_weather.temperature),
             icon,
+            source: Source::OpenMeteo,
+            code: result.current_weather.weathercode,
+            condition: describe(result.current_weather.weathercode).to_string(),
+            is_day: time_of_day == TimeOfDay::Day,
+            humidity: result.current.relative_humidity_2m,
+            wind_kph: result.current_weather.windspeed,
+            pressure_hpa: result.current.pressure_msl,
+            precip_mm: result.current.precipitation,
+            visibility_km: result
+                .current
+                .visibility
+                .map(|visibility| visibility / 1000.0),
+            derived: Derived::default(),
+            air_quality: None,
         }
     }
 }
 
+/// Describes a WMO weather interpretation code as documented at
+/// <https://open-meteo.com/en/docs>.
+fn describe(code: i32) -> &'static str {
+    match code {
+        0 => "Clear sky",
+        1 => "Mainly clear",
+        2 => "Partly cloudy",
+        3 => "Overcast",
+        45 => "Fog",
+        48 => "Depositing rime fog",
+        51 => "Light drizzle",
+        53 => "Moderate drizzle",
+        55 => "Dense drizzle",
+        56 => "Light freezing drizzle",
+        57 => "Dense freezing drizzle",
+        61 => "Slight rain",
+        63 => "Moderate rain",
+        65 => "Heavy rain",
+        66 => "Light freezing rain",
+        67 => "Heavy freezing rain",
+        71 => "Slight snow fall",
+        73 => "Moderate snow fall",
+        75 => "Heavy snow fall",
+        77 => "Snow grains",
+        80 => "Slight rain showers",
+        81 => "Moderate rain showers",
+        82 => "Violent rain showers",
+        85 => "Slight snow showers",
+        86 => "Heavy snow showers",
+        95 => "Thunderstorm",
+        96 => "Thunderstorm with slight hail",
+        99 => "Thunderstorm with heavy hail",
+        _ => "Unknown",
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -92,8 +203,8 @@ mod test {
         assert_eq!(client.query.len(), 4);
         assert_eq!(client.query[0].0, "current_weather");
         assert_eq!(client.query[0].1, "true");
-        assert_eq!(client.query[1].0, "temperature_unit");
-        assert_eq!(client.query[1].1, "fahrenheit");
+        assert_eq!(client.query[1].0, "current");
+        assert_eq!(client.query[1].1, CURRENT);
         assert_eq!(client.query[2].0, "latitude");
         assert_eq!(client.query[2].1, location.latitude);
         assert_eq!(client.query[3].0, "longitude");
@@ -103,16 +214,52 @@ mod test {
     #[test]
     fn it_converts_response_to_current_conditions() {
         let response = Response {
+            latitude: None,
+            longitude: None,
+            current: Current {
+                relative_humidity_2m: Some(80.0),
+                visibility: Some(16_000.0),
+                ..Default::default()
+            },
             current_weather: CurrentWeather {
                 temperature: 10.0,
+                windspeed: Some(12.0),
                 weathercode: 85,
-                is_day: 1,
+                is_day: Some(1),
+                time: None,
             },
         };
         let conditions = CurrentConditions::from(response);
 
         assert!((conditions.temp_c - 10.0).abs() < f32::EPSILON);
-        assert!((conditions.temp_f - 10.0).abs() < f32::EPSILON);
+        assert!((conditions.temp_f - 50.0).abs() < f32::EPSILON);
+        assert_eq!(conditions.humidity, Some(80.0));
+        assert_eq!(conditions.wind_kph, Some(12.0));
+        assert_eq!(conditions.visibility_km, Some(16.0));
         assert_eq!(conditions.icon, " ");
     }
+    #[test]
+    fn it_derives_is_day_at_reported_time() {
+        // Charlotte on 2024-06-21 at 16:00Z and 06:00Z
+        for (time, is_day) in
+            [("2024-06-21T16:00", true), ("2024-06-21T06:00", false)]
+        {
+            let response: Response = serde_json::from_str(&format!(
+                r#"{{
+                    "latitude": 35.23,
+                    "longitude": -80.84,
+                    "current_weather": {{
+                        "temperature": 20.0,
+                        "weathercode": 0,
+                        "time": "{time}"
+                    }}
+                }}"#
+            ))
+            .unwrap();
+
+            let conditions = CurrentConditions::from(response);
+
+            assert_eq!(conditions.is_day, is_day, "{time}");
+        }
+    }
 }
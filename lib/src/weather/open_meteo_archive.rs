@@ -0,0 +1,231 @@
+use serde::Deserialize;
+
+use crate::{
+    config::RequestPolicy,
+    history::{Archive, Day, Hour},
+    location::Location,
+    Config, Error,
+};
+
+const PROVIDER: &str = "open_meteo_archive";
+
+// https://archive-api.open-meteo.com/v1/archive?latitude=35.15&longitude=-80.81&start_date=2024-01-01&end_date=2024-01-01&daily=weather_code,temperature_2m_max&hourly=temperature_2m
+// {
+//   "latitude": 35.16,
+//   "longitude": -80.8,
+//   "timezone": "GMT",
+//   "hourly": {
+//     "time": ["2024-01-01T00:00", "2024-01-01T01:00", ...],
+//     "temperature_2m": [8.1, 7.6, ...]
+//   },
+//   "daily": {
+//     "time": ["2024-01-01"],
+//     "weather_code": [61],
+//     "temperature_2m_max": [11.2]
+//   }
+// }
+//
+// values are `null` where the archive has no data
+
+const DAILY: &str = "weather_code,temperature_2m_max,temperature_2m_min,temperature_2m_mean,precipitation_sum";
+const HOURLY: &str =
+    "weather_code,temperature_2m,relative_humidity_2m,precipitation";
+
+pub struct Client {
+    policy: RequestPolicy,
+    query: Vec<(String, String)>,
+}
+
+impl Client {
+    /// Creates a client fetching the days from `from` to `to` (inclusive,
+    /// `YYYY-MM-DD`) for `location`.
+    pub fn new(
+        config: &Config,
+        location: &Location,
+        from: &str,
+        to: &str,
+    ) -> Self {
+        Self {
+            policy: config.network.policy(PROVIDER),
+            query: vec![
+                ("latitude".to_string(), location.latitude.clone()),
+                ("longitude".to_string(), location.longitude.clone()),
+                ("start_date".to_string(), from.to_string()),
+                ("end_date".to_string(), to.to_string()),
+                ("daily".to_string(), DAILY.to_string()),
+                ("hourly".to_string(), HOURLY.to_string()),
+                ("timezone".to_string(), "auto".to_string()),
+            ],
+        }
+    }
+}
+
+impl crate::api::Fetchable<Response, Archive> for Client {
+    fn provider(&self) -> &'static str {
+        PROVIDER
+    }
+
+    fn url(&self) -> &'static str {
+        "https://archive-api.open-meteo.com/v1/archive"
+    }
+
+    fn policy(&self) -> RequestPolicy {
+        self.policy
+    }
+
+    fn query(&self) -> Option<&Vec<(String, String)>> {
+        Some(&self.query)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Daily {
+    time: Vec<String>,
+    weather_code: Vec<Option<i32>>,
+    temperature_2m_max: Vec<Option<f64>>,
+    temperature_2m_min: Vec<Option<f64>>,
+    temperature_2m_mean: Vec<Option<f64>>,
+    precipitation_sum: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Hourly {
+    time: Vec<String>,
+    weather_code: Vec<Option<i32>>,
+    temperature_2m: Vec<Option<f64>>,
+    relative_humidity_2m: Vec<Option<f64>>,
+    precipitation: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Response {
+    #[serde(default)]
+    daily: Daily,
+    #[serde(default)]
+    hourly: Hourly,
+}
+
+/// Value at `index` of a column, missing values (short columns included) are
+/// `None`.
+fn at<T: Copy>(column: &[Option<T>], index: usize) -> Option<T> {
+    column.get(index).copied().flatten()
+}
+
+impl TryFrom<Response> for Archive {
+    type Error = Error;
+
+    fn try_from(response: Response) -> Result<Self, Self::Error> {
+        let Response { daily, hourly } = response;
+
+        if daily.time.is_empty() && hourly.time.is_empty() {
+            return Err(Error::Parse {
+                provider: PROVIDER.to_string(),
+                message: "no daily or hourly data".to_string(),
+            });
+        }
+
+        let days = daily
+            .time
+            .iter()
+            .enumerate()
+            .map(|(i, date)| Day {
+                date: date.clone(),
+                weather_code: at(&daily.weather_code, i),
+                temp_max: at(&daily.temperature_2m_max, i),
+                temp_min: at(&daily.temperature_2m_min, i),
+                temp_mean: at(&daily.temperature_2m_mean, i),
+                precipitation: at(&daily.precipitation_sum, i),
+            })
+            .collect();
+        let hours = hourly
+            .time
+            .iter()
+            .enumerate()
+            .map(|(i, time)| Hour {
+                time: time.clone(),
+                weather_code: at(&hourly.weather_code, i),
+                temp: at(&hourly.temperature_2m, i),
+                relative_humidity: at(&hourly.relative_humidity_2m, i),
+                precipitation: at(&hourly.precipitation, i),
+            })
+            .collect();
+
+        Ok(Self { days, hours })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_builds_query() {
+        let location = Location {
+            latitude: "35.15".to_string(),
+            longitude: "-80.81".to_string(),
+            ..Default::default()
+        };
+        let client = Client::new(
+            &Config::default(),
+            &location,
+            "2024-01-01",
+            "2024-01-31",
+        );
+
+        assert!(client
+            .query
+            .contains(&("start_date".to_string(), "2024-01-01".to_string())));
+        assert!(client
+            .query
+            .contains(&("end_date".to_string(), "2024-01-31".to_string())));
+        assert!(client
+            .query
+            .contains(&("latitude".to_string(), "35.15".to_string())));
+    }
+
+    #[test]
+    fn it_converts_response() {
+        let response: Response = serde_json::from_str(
+            r#"{
+                "daily": {
+                    "time": ["2024-01-01", "2024-01-02"],
+                    "weather_code": [61, null],
+                    "temperature_2m_max": [52.1, 48.0],
+                    "temperature_2m_min": [40.3],
+                    "temperature_2m_mean": [46.0, 44.2],
+                    "precipitation_sum": [0.4, 0.0]
+                },
+                "hourly": {
+                    "time": ["2024-01-01T00:00"],
+                    "weather_code": [3],
+                    "temperature_2m": [44.6],
+                    "relative_humidity_2m": [91],
+                    "precipitation": [0.0]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let archive = Archive::try_from(response).unwrap();
+
+        assert_eq!(archive.days.len(), 2);
+        assert_eq!(archive.days[0].weather_code, Some(61));
+        assert_eq!(archive.days[1].weather_code, None);
+        assert_eq!(archive.days[1].temp_min, None);
+        assert_eq!(archive.hours.len(), 1);
+        assert_eq!(archive.hours[0].time, "2024-01-01T00:00");
+        assert_eq!(archive.hours[0].relative_humidity, Some(91.0));
+    }
+
+    #[test]
+    fn it_rejects_empty_response() {
+        let response: Response = serde_json::from_str("{}").unwrap();
+
+        assert!(matches!(
+            Archive::try_from(response),
+            Err(Error::Parse { .. })
+        ));
+    }
+}
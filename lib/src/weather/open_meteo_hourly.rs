@@ -0,0 +1,122 @@
+use serde::Deserialize;
+
+use crate::{config::RequestPolicy, location::Location, Config};
+
+const PROVIDER: &str = "open_meteo";
+
+// https://api.open-meteo.com/v1/forecast?latitude=35.15&longitude=-80.81&hourly=precipitation_probability&forecast_hours=3
+// {
+//   "latitude": 35.16,
+//   "longitude": -80.8,
+//   "hourly": {
+//     "time": ["2024-06-01T14:00", "2024-06-01T15:00", "2024-06-01T16:00"],
+//     "precipitation_probability": [10, 45, 80]
+//   }
+// }
+//
+// hours start from the current one, values can be `null`
+
+/// Hourly forecast starting from the current hour.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Hourly {
+    /// Percent, missing hours are left out.
+    pub precipitation_probability: Vec<f64>,
+}
+
+pub struct Client {
+    policy: RequestPolicy,
+    query: Vec<(String, String)>,
+}
+
+impl Client {
+    /// Creates a client fetching the next `hours` hours for `location`.
+    pub fn new(config: &Config, location: &Location, hours: u32) -> Self {
+        Self {
+            policy: config.network.policy(PROVIDER),
+            query: vec![
+                ("latitude".to_string(), location.latitude.clone()),
+                ("longitude".to_string(), location.longitude.clone()),
+                (
+                    "hourly".to_string(),
+                    "precipitation_probability".to_string(),
+                ),
+                ("forecast_hours".to_string(), hours.to_string()),
+            ],
+        }
+    }
+}
+
+impl crate::api::Fetchable<Response, Hourly> for Client {
+    fn provider(&self) -> &'static str {
+        PROVIDER
+    }
+
+    fn url(&self) -> &'static str {
+        "https://api.open-meteo.com/v1/forecast"
+    }
+
+    fn policy(&self) -> RequestPolicy {
+        self.policy
+    }
+
+    fn query(&self) -> Option<&Vec<(String, String)>> {
+        Some(&self.query)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct HourlyColumns {
+    precipitation_probability: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Response {
+    #[serde(default)]
+    hourly: HourlyColumns,
+}
+
+impl From<Response> for Hourly {
+    fn from(response: Response) -> Self {
+        Self {
+            precipitation_probability: response
+                .hourly
+                .precipitation_probability
+                .into_iter()
+                .flatten()
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_builds_query() {
+        let client = Client::new(&Config::default(), &Location::default(), 3);
+
+        assert!(client
+            .query
+            .contains(&("forecast_hours".to_string(), "3".to_string())));
+    }
+
+    #[test]
+    fn it_converts_response() {
+        let response: Response = serde_json::from_str(
+            r#"{
+                "hourly": {
+                    "time": ["2024-06-01T14:00", "2024-06-01T15:00", "2024-06-01T16:00"],
+                    "precipitation_probability": [10, null, 80]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Hourly::from(response).precipitation_probability,
+            vec![10.0, 80.0]
+        );
+    }
+}
@@ -1,13 +1,20 @@
 use std::convert::From;
 
 use serde::Deserialize;
-use thiserror::Error;
 
-use super::CurrentConditions;
-use crate::{config::Config, icons::TimeOfDay, location::Location};
+use super::{CurrentConditions, Derived};
+use crate::{
+    air_quality::{weather_api::WeatherAPIResultAirQuality, AirQuality},
+    config::{Config, RequestPolicy},
+    icons::TimeOfDay,
+    location::Location,
+};
+
+const PROVIDER: &str = "weatherapi";
 
 pub struct Client {
     is_valid: bool,
+    policy: RequestPolicy,
     query: Vec<(String, String)>,
 }
 
@@ -15,18 +22,29 @@ impl Client {
     pub fn new(config: &Config, location: &Location) -> Self {
         let key = config.weatherapi_token.clone();
         let is_valid = key.is_some();
+        let mut query = vec![
+            ("key".to_string(), key.unwrap_or_default()),
+            ("q".to_string(), location.loc.clone()),
+        ];
+
+        // saves a second request for the air quality
+        if config.show_air_quality {
+            query.push(("aqi".to_string(), "yes".to_string()));
+        }
 
         Self {
             is_valid,
-            query: vec![
-                ("key".to_string(), key.unwrap_or_default()),
-                ("q".to_string(), location.loc.clone()),
-            ],
+            policy: config.network.policy(PROVIDER),
+            query,
         }
     }
 }
 
 impl crate::api::Fetchable<Response, CurrentConditions> for Client {
+    fn provider(&self) -> &'static str {
+        PROVIDER
+    }
+
     fn url(&self) -> &'static str {
         "http://api.weatherapi.com/v1/current.json"
     }
@@ -35,68 +53,125 @@ impl crate::api::Fetchable<Response, CurrentConditions> for Client {
         self.is_valid
     }
 
-    fn query(&self) -> Option<&Vec<(String, String)>> {
-        Some(&self.query)
-    }
-}
-
-#[derive(Error, Debug, PartialEq)]
-pub enum FetchConditionsError {
-    #[error("unknown error fetching weather conditions")]
-    Unknown,
-}
-
-impl std::convert::From<ureq::Error> for FetchConditionsError {
-    fn from(_: ureq::Error) -> Self {
-        Self::Unknown
+    fn policy(&self) -> RequestPolicy {
+        self.policy
     }
-}
 
-impl std::convert::From<std::io::Error> for FetchConditionsError {
-    fn from(_: std::io::Error) -> Self {
-        Self::Unknown
+    fn query(&self) -> Option<&Vec<(String, String)>> {
+        Some(&self.query)
     }
 }
 
 /// Response represented as JSON response
 /// ```json
 /// {
+///   "location": {
+///     "lat": f64,
+///     "lon": f64
+///   },
 ///   "current": {
+///     "last_updated_epoch": i64,
 ///     "condition": {
 ///       "code": i32
 ///     },
 ///     "temp_f": f32,
-///     "is_day": u8
+///     "is_day": u8,
+///     "humidity": f32,
+///     "wind_kph": f32,
+///     "pressure_mb": f32,
+///     "precip_mm": f32,
+///     "vis_km": f32,
+///     "dewpoint_c": f32,
+///     "heatindex_c": f32,
+///     "windchill_c": f32,
+///     "air_quality": { ... } (with aqi=yes)
 ///   }
 /// }
 /// ```
 #[derive(Debug, Deserialize)]
 pub struct Response {
+    #[serde(default)]
+    location: Option<WeatherAPIResultLocation>,
     current: WeatherAPIResultCurrent,
 }
 
+#[derive(Debug, Deserialize)]
+struct WeatherAPIResultLocation {
+    lat: f64,
+    lon: f64,
+}
+
 #[derive(Debug, Deserialize)]
 struct WeatherAPIResultCurrent {
     condition: WeatherAPIResultCondition,
+    /// When the conditions were observed, in Unix seconds.
+    #[serde(default)]
+    last_updated_epoch: Option<i64>,
     temp_c: f32,
     temp_f: f32,
-    is_day: u8,
+    #[serde(default)]
+    is_day: Option<u8>,
+    #[serde(default)]
+    humidity: Option<f32>,
+    #[serde(default)]
+    wind_kph: Option<f32>,
+    #[serde(default)]
+    pressure_mb: Option<f32>,
+    #[serde(default)]
+    precip_mm: Option<f32>,
+    #[serde(default)]
+    vis_km: Option<f32>,
+    #[serde(default)]
+    dewpoint_c: Option<f32>,
+    #[serde(default)]
+    heatindex_c: Option<f32>,
+    #[serde(default)]
+    windchill_c: Option<f32>,
+    #[serde(default)]
+    air_quality: Option<WeatherAPIResultAirQuality>,
 }
 
 #[derive(Debug, Deserialize)]
 struct WeatherAPIResultCondition {
     code: i32,
+    #[serde(default)]
+    text: String,
 }
 
 impl From<Response> for CurrentConditions {
     fn from(result: Response) -> Self {
-        let icon = TimeOfDay::from(result.current.is_day)
+        let time_of_day = TimeOfDay::reported(
+            result.current.is_day,
+            result.location.as_ref().map(|location| location.lat),
+            result.location.as_ref().map(|location| location.lon),
+            result
+                .current
+                .last_updated_epoch
+                .unwrap_or_else(crate::astro::now),
+        );
+        let icon = time_of_day
             .icon(&super::Source::WeatherAPI, result.current.condition.code);
 
         Self {
             temp_c: result.current.temp_c,
             temp_f: result.current.temp_f,
             icon,
+            source: super::Source::WeatherAPI,
+            code: result.current.condition.code,
+            condition: result.current.condition.text,
+            is_day: time_of_day == TimeOfDay::Day,
+            humidity: result.current.humidity,
+            wind_kph: result.current.wind_kph,
+            pressure_hpa: result.current.pressure_mb,
+            precip_mm: result.current.precip_mm,
+            visibility_km: result.current.vis_km,
+            derived: Derived {
+                dew_point_c: result.current.dewpoint_c,
+                heat_index_c: result.current.heatindex_c,
+                wind_chill_c: result.current.windchill_c,
+                ..Default::default()
+            },
+            air_quality: result.current.air_quality.map(AirQuality::from),
         }
     }
 }
@@ -126,14 +201,64 @@ mod test {
         );
     }
 
+    #[test]
+    fn it_requests_air_quality_when_shown() {
+        let config = Config {
+            weatherapi_token: Some("token123".to_string()),
+            show_air_quality: true,
+            ..Default::default()
+        };
+        let client = Client::new(&config, &Location::default());
+
+        assert!(client
+            .query
+            .contains(&("aqi".to_string(), "yes".to_string())));
+    }
+
+    #[test]
+    fn it_converts_air_quality_with_conditions() {
+        let response: Response = serde_json::from_str(
+            r#"{
+                "current": {
+                    "condition": {"code": 1000},
+                    "temp_c": 10.0,
+                    "temp_f": 50.0,
+                    "is_day": 1,
+                    "air_quality": {"pm2_5": 45.4, "pm10": 54.0}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let conditions = CurrentConditions::from(response);
+        let air_quality = conditions.air_quality.unwrap();
+
+        assert_eq!(air_quality.us_aqi, Some(125));
+        assert_eq!(air_quality.provider, PROVIDER);
+    }
+
     #[test]
     fn test_weatherapi_from() {
         let response = Response {
+            location: None,
             current: WeatherAPIResultCurrent {
-                condition: WeatherAPIResultCondition { code: 1087 },
+                condition: WeatherAPIResultCondition {
+                    code: 1087,
+                    text: "Thundery outbreaks possible".to_string(),
+                },
+                last_updated_epoch: None,
                 temp_c: 10.0,
                 temp_f: 50.0,
-                is_day: 0,
+                is_day: Some(0),
+                humidity: None,
+                wind_kph: None,
+                pressure_mb: None,
+                precip_mm: None,
+                vis_km: None,
+                dewpoint_c: None,
+                heatindex_c: None,
+                windchill_c: None,
+                air_quality: None,
             },
         };
         let conditions = CurrentConditions::from(response);
@@ -141,4 +266,27 @@ mod test {
         assert!((conditions.temp_f - 50.0).abs() < f32::EPSILON);
         assert_eq!(conditions.icon, "".to_string());
     }
+
+    #[test]
+    fn it_derives_is_day_when_omitted() {
+        // Charlotte on 2024-06-21 at 16:00Z and 06:00Z
+        for (time, is_day) in [(1_718_985_600, true), (1_718_949_600, false)] {
+            let response: Response = serde_json::from_str(&format!(
+                r#"{{
+                    "location": {{"lat": 35.23, "lon": -80.84}},
+                    "current": {{
+                        "last_updated_epoch": {time},
+                        "condition": {{"code": 1000}},
+                        "temp_c": 10.0,
+                        "temp_f": 50.0
+                    }}
+                }}"#
+            ))
+            .unwrap();
+
+            let conditions = CurrentConditions::from(response);
+
+            assert_eq!(conditions.is_day, is_day, "{time}");
+        }
+    }
 }
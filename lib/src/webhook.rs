@@ -0,0 +1,273 @@
+use std::fmt::Write;
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::{
+    api::{agent, with_retries},
+    config::Config,
+    rules::Event,
+    Error, Result,
+};
+
+const PROVIDER: &str = "webhook";
+
+/// Header holding the `EventKind` of the delivered event.
+pub const EVENT_HEADER: &str = "X-Conditions-Event";
+/// Header holding `sha256=` followed by the hex encoded HMAC-SHA256 of the
+/// timestamp, a `.` and the body, keyed with the webhook's secret.
+pub const SIGNATURE_HEADER: &str = "X-Conditions-Signature";
+/// Header holding the time of the delivery attempt in seconds since the
+/// Unix epoch, covered by the signature so that receivers can reject
+/// replayed requests.
+pub const TIMESTAMP_HEADER: &str = "X-Conditions-Timestamp";
+
+/// A URL watch mode sends its events to, configured as
+///
+/// ```toml
+/// [[webhooks]]
+/// url = "https://chat.example.com/hooks/weather"
+/// secret = "shared with the receiver"
+/// ```
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Webhook {
+    pub url: String,
+    /// Key used to sign the payload, unsigned when `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+impl Webhook {
+    /// POSTs `event` as JSON, retrying transient failures as configured by
+    /// the `webhook` network policy.
+    ///
+    /// # Errors
+    ///
+    /// The `crate::Error` describing the failure of the last attempt (eg.
+    /// `Error::Network`).
+    #[allow(clippy::result_large_err)]
+    pub fn deliver(&self, config: &Config, event: &Event) -> Result<()> {
+        let policy = config.network.policy(PROVIDER);
+        let body =
+            serde_json::to_string(event).map_err(|err| Error::Parse {
+                provider: PROVIDER.to_string(),
+                message: err.to_string(),
+            })?;
+        let agent = agent(&policy);
+
+        with_retries(&policy, || {
+            // stamped per attempt, retries may come long after the first
+            let timestamp = crate::astro::now();
+            let mut request = agent
+                .post(&self.url)
+                .set("Content-Type", "application/json")
+                .set(EVENT_HEADER, &event.kind.to_string())
+                .set(TIMESTAMP_HEADER, &timestamp.to_string());
+
+            if let Some(secret) = &self.secret {
+                request = request.set(
+                    SIGNATURE_HEADER,
+                    &sign(secret, timestamp, body.as_bytes()),
+                );
+            }
+
+            request.send_string(&body)
+        })
+        .map(|_| ())
+        .map_err(|err| {
+            log::debug!("delivery to {} failed: {err}", self.url);
+
+            Error::from_ureq(PROVIDER, &err)
+        })
+    }
+}
+
+/// Signs `timestamp` and `body` with `secret`, in the format of
+/// `SIGNATURE_HEADER`.
+///
+/// # Panics
+///
+/// Never, HMAC accepts keys of any length.
+#[must_use]
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("hmac key of any length");
+
+    mac.update(format!("{timestamp}.").as_bytes());
+    mac.update(body);
+
+    mac.finalize().into_bytes().iter().fold(
+        "sha256=".to_string(),
+        |mut signature, byte| {
+            let _ = write!(signature, "{byte:02x}");
+
+            signature
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::mpsc,
+        thread,
+    };
+
+    use super::*;
+    use crate::{config::RequestPolicy, rules::EventKind};
+
+    /// A request as received by `serve`, header names are lowercase.
+    struct Request {
+        headers: HashMap<String, String>,
+        body: String,
+    }
+
+    /// Answers one connection per status in `statuses`, sending each request
+    /// it received on the returned channel.
+    fn serve(statuses: Vec<u16>) -> (String, mpsc::Receiver<Request>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            for status in statuses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut headers = HashMap::new();
+                let mut line = String::new();
+
+                reader.read_line(&mut line).unwrap();
+
+                loop {
+                    line.clear();
+                    reader.read_line(&mut line).unwrap();
+
+                    let Some((name, value)) = line.trim_end().split_once(": ")
+                    else {
+                        break;
+                    };
+
+                    headers.insert(name.to_lowercase(), value.to_string());
+                }
+
+                let length = headers["content-length"].parse().unwrap();
+                let mut body = vec![0; length];
+
+                reader.read_exact(&mut body).unwrap();
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                )
+                .unwrap();
+                sender
+                    .send(Request {
+                        headers,
+                        body: String::from_utf8(body).unwrap(),
+                    })
+                    .unwrap();
+            }
+        });
+
+        (url, receiver)
+    }
+
+    fn config() -> Config {
+        let policy = RequestPolicy {
+            retries: 1,
+            backoff_ms: 1,
+            max_backoff_ms: 1,
+            ..Default::default()
+        };
+
+        Config {
+            network: crate::config::Network {
+                defaults: policy,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn event() -> Event {
+        Event {
+            kind: EventKind::Rule,
+            rule: Some("severe".to_string()),
+            location: "Charlotte".to_string(),
+            message: "Tornado Warning until 2:45PM".to_string(),
+            value: None,
+            alert: None,
+            command: None,
+        }
+    }
+
+    #[test]
+    fn it_signs_timestamp_and_body() {
+        assert_eq!(
+            sign(
+                "key",
+                1_700_000_000,
+                b"The quick brown fox jumps over the lazy dog"
+            ),
+            "sha256=2f658d6aef4f246e91cd741bbcded7479e9605f9d41c9e248122a117e0e1765b"
+        );
+    }
+
+    #[test]
+    fn it_delivers_signed_event() {
+        let (url, requests) = serve(vec![200]);
+        let webhook = Webhook {
+            url,
+            secret: Some("s3cret".to_string()),
+        };
+
+        webhook.deliver(&config(), &event()).unwrap();
+
+        let request = requests.recv().unwrap();
+
+        assert_eq!(request.body, serde_json::to_string(&event()).unwrap());
+        assert_eq!(request.headers["content-type"], "application/json");
+        assert_eq!(request.headers["x-conditions-event"], "rule");
+
+        let timestamp: i64 =
+            request.headers["x-conditions-timestamp"].parse().unwrap();
+
+        assert!((crate::astro::now() - timestamp).abs() < 60);
+        assert_eq!(
+            request.headers["x-conditions-signature"],
+            sign("s3cret", timestamp, request.body.as_bytes())
+        );
+    }
+
+    #[test]
+    fn it_retries_and_skips_signature_without_secret() {
+        let (url, requests) = serve(vec![503, 204]);
+        let webhook = Webhook { url, secret: None };
+
+        webhook.deliver(&config(), &event()).unwrap();
+
+        assert!(requests.recv().is_ok());
+        assert!(!requests
+            .recv()
+            .unwrap()
+            .headers
+            .contains_key("x-conditions-signature"));
+    }
+
+    #[test]
+    fn it_fails_after_retries() {
+        let (url, _requests) = serve(vec![500, 500]);
+        let webhook = Webhook { url, secret: None };
+
+        assert!(matches!(
+            webhook.deliver(&config(), &event()),
+            Err(Error::Network {
+                status: Some(500),
+                ..
+            })
+        ));
+    }
+}